@@ -5,6 +5,27 @@ mod m20230721_114813_art_and_literature_tables;
 mod m20230721_151213_vote_tables;
 mod m20230722_010433_art_description_column;
 mod m20230722_013515_nsfw_columns;
+mod m20230801_000000_contest_table;
+mod m20230802_000000_hidden_columns;
+mod m20230803_000000_submission_limit_indexes;
+mod m20230804_000000_submission_table;
+mod m20230805_000000_voting_method;
+mod m20230806_000000_tie_break;
+mod m20230807_000000_blind_mode;
+mod m20230808_000000_jury_score_table;
+mod m20230809_000000_submission_storage_keys;
+mod m20230810_000000_submission_mime_type;
+mod m20230811_000000_submission_hashes;
+mod m20230812_000000_submission_derivative_table;
+mod m20230813_000000_submission_private_data_key;
+mod m20230814_000000_submission_pages;
+mod m20230815_000000_job_table;
+mod m20230816_000000_perceptual_hashes;
+mod m20230817_000000_text_format;
+mod m20230818_000000_session_table;
+mod m20230819_000000_account_ids;
+mod m20230820_000000_submission_duplicate_table;
+mod m20230821_000000_account_table;
 
 pub struct Migrator;
 
@@ -17,6 +38,27 @@ impl MigratorTrait for Migrator {
             Box::new(m20230721_151213_vote_tables::Migration),
             Box::new(m20230722_010433_art_description_column::Migration),
             Box::new(m20230722_013515_nsfw_columns::Migration),
+            Box::new(m20230801_000000_contest_table::Migration),
+            Box::new(m20230802_000000_hidden_columns::Migration),
+            Box::new(m20230803_000000_submission_limit_indexes::Migration),
+            Box::new(m20230804_000000_submission_table::Migration),
+            Box::new(m20230805_000000_voting_method::Migration),
+            Box::new(m20230806_000000_tie_break::Migration),
+            Box::new(m20230807_000000_blind_mode::Migration),
+            Box::new(m20230808_000000_jury_score_table::Migration),
+            Box::new(m20230809_000000_submission_storage_keys::Migration),
+            Box::new(m20230810_000000_submission_mime_type::Migration),
+            Box::new(m20230811_000000_submission_hashes::Migration),
+            Box::new(m20230812_000000_submission_derivative_table::Migration),
+            Box::new(m20230813_000000_submission_private_data_key::Migration),
+            Box::new(m20230814_000000_submission_pages::Migration),
+            Box::new(m20230815_000000_job_table::Migration),
+            Box::new(m20230816_000000_perceptual_hashes::Migration),
+            Box::new(m20230817_000000_text_format::Migration),
+            Box::new(m20230818_000000_session_table::Migration),
+            Box::new(m20230819_000000_account_ids::Migration),
+            Box::new(m20230820_000000_submission_duplicate_table::Migration),
+            Box::new(m20230821_000000_account_table::Migration),
         ]
     }
 }
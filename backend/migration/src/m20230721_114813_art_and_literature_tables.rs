@@ -88,6 +88,8 @@ pub enum Literature {
     AuthorHandle,
     AuthorInstance,
     IsNsfw,
+    ContestId,
+    IsHidden,
 }
 
 #[derive(Iden)]
@@ -101,4 +103,6 @@ pub enum Art {
     AuthorInstance,
     Description,
     IsNsfw,
+    ContestId,
+    IsHidden,
 }
@@ -95,19 +95,21 @@ impl MigrationTrait for Migration {
 }
 
 #[derive(Iden)]
-enum LiteratureVote {
+pub enum LiteratureVote {
     Table,
     Id,
     Handle,
     Instance,
     LiteratureId,
+    ContestId,
 }
 
 #[derive(Iden)]
-enum ArtVote {
+pub enum ArtVote {
     Table,
     Id,
     Handle,
     Instance,
     ArtId,
+    ContestId,
 }
@@ -0,0 +1,260 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230721_114813_art_and_literature_tables::{Art, Literature},
+    m20230721_151213_vote_tables::{ArtVote, LiteratureVote},
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Contest::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Contest::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Contest::Slug)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Contest::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(Contest::SubmissionOpenAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Contest::SubmissionCloseAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Contest::VotingOpenAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Contest::VotingCloseAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Contest::LiteratureEnabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .col(
+                        ColumnDef::new(Contest::ArtEnabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Submissions made before contests existed belong to a single, already finished contest.
+        // Its name and schedule are overwritten on startup if it is still configured by env vars.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"INSERT INTO "contest" ("slug", "name", "submission_open_at", "submission_close_at", "voting_open_at", "voting_close_at")
+                SELECT 'default', 'default', now(), now(), now(), now()
+                WHERE EXISTS (SELECT 1 FROM "literature") OR EXISTS (SELECT 1 FROM "art")"#,
+            )
+            .await?;
+
+        add_contest_id_column(manager, Literature::Table, Literature::ContestId).await?;
+        add_contest_id_column(manager, Art::Table, Art::ContestId).await?;
+        add_contest_id_column(manager, LiteratureVote::Table, LiteratureVote::ContestId).await?;
+        add_contest_id_column(manager, ArtVote::Table, ArtVote::ContestId).await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"ALTER TABLE "literature" DROP CONSTRAINT IF EXISTS "literature_author_handle_author_instance_key""#,
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"ALTER TABLE "art" DROP CONSTRAINT IF EXISTS "art_author_handle_author_instance_key""#,
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-literature-contest_id-author_handle-author_instance")
+                    .table(Literature::Table)
+                    .col(Literature::ContestId)
+                    .col(Literature::AuthorHandle)
+                    .col(Literature::AuthorInstance)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-art-contest_id-author_handle-author_instance")
+                    .table(Art::Table)
+                    .col(Art::ContestId)
+                    .col(Art::AuthorHandle)
+                    .col(Art::AuthorInstance)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-literature-contest_id-author_handle-author_instance")
+                    .table(Literature::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-art-contest_id-author_handle-author_instance")
+                    .table(Art::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        for (table, column) in [
+            (
+                Literature::Table.into_iden(),
+                Literature::ContestId.into_iden(),
+            ),
+            (Art::Table.into_iden(), Art::ContestId.into_iden()),
+            (
+                LiteratureVote::Table.into_iden(),
+                LiteratureVote::ContestId.into_iden(),
+            ),
+            (ArtVote::Table.into_iden(), ArtVote::ContestId.into_iden()),
+        ] {
+            manager
+                .alter_table(Table::alter().table(table).drop_column(column).to_owned())
+                .await?;
+        }
+
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"ALTER TABLE "literature" ADD CONSTRAINT "literature_author_handle_author_instance_key" UNIQUE ("author_handle", "author_instance")"#,
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"ALTER TABLE "art" ADD CONSTRAINT "art_author_handle_author_instance_key" UNIQUE ("author_handle", "author_instance")"#,
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Contest::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+async fn add_contest_id_column(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden,
+    column: impl IntoIden,
+) -> Result<(), DbErr> {
+    let table = table.into_iden();
+    let column = column.into_iden();
+
+    manager
+        .alter_table(
+            Table::alter()
+                .table(table.clone())
+                .add_column_if_not_exists(ColumnDef::new(column.clone()).integer())
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .exec_stmt(
+            Query::update()
+                .table(table.clone())
+                .value(
+                    column.clone(),
+                    SimpleExpr::SubQuery(
+                        None,
+                        Box::new(
+                            Query::select()
+                                .column(Contest::Id)
+                                .from(Contest::Table)
+                                .and_where(Expr::col(Contest::Slug).eq("default"))
+                                .to_owned()
+                                .into_sub_query_statement(),
+                        ),
+                    ),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .alter_table(
+            Table::alter()
+                .table(table.clone())
+                .modify_column(ColumnDef::new(column.clone()).integer().not_null())
+                .add_foreign_key(
+                    TableForeignKey::new()
+                        .from_tbl(table)
+                        .from_col(column)
+                        .to_tbl(Contest::Table)
+                        .to_col(Contest::Id)
+                        .on_delete(ForeignKeyAction::Cascade)
+                        .on_update(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Iden)]
+pub enum Contest {
+    Table,
+    Id,
+    Slug,
+    Name,
+    SubmissionOpenAt,
+    SubmissionCloseAt,
+    VotingOpenAt,
+    VotingCloseAt,
+    LiteratureEnabled,
+    ArtEnabled,
+    VotingMethod,
+    TieBreak,
+    Blind,
+    JudgingOpenAt,
+    JudgingCloseAt,
+    JuryWeight,
+}
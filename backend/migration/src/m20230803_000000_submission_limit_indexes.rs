@@ -0,0 +1,54 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230721_114813_art_and_literature_tables::{Art, Literature};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Submission count per user is checked against the configured limit instead
+        replace_author_index(manager, Literature::Table, false).await?;
+        replace_author_index(manager, Art::Table, false).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        replace_author_index(manager, Literature::Table, true).await?;
+        replace_author_index(manager, Art::Table, true).await?;
+
+        Ok(())
+    }
+}
+
+async fn replace_author_index(
+    manager: &SchemaManager<'_>,
+    table: impl IntoIden,
+    unique: bool,
+) -> Result<(), DbErr> {
+    let table = table.into_iden();
+    let name = format!(
+        "idx-{}-contest_id-author_handle-author_instance",
+        table.to_string()
+    );
+
+    manager
+        .drop_index(Index::drop().name(&name).table(table.clone()).to_owned())
+        .await?;
+
+    let mut index = Index::create();
+    index
+        .name(&name)
+        .table(table)
+        .col(Alias::new("contest_id"))
+        .col(Alias::new("author_handle"))
+        .col(Alias::new("author_instance"));
+    if unique {
+        index.unique();
+    }
+    manager.create_index(index.to_owned()).await?;
+
+    Ok(())
+}
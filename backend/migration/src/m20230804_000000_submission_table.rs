@@ -0,0 +1,323 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230801_000000_contest_table::Contest;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Submission::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Submission::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Submission::ContestId).integer().not_null())
+                    .col(ColumnDef::new(Submission::Category).string().not_null())
+                    .col(ColumnDef::new(Submission::Title).string().not_null())
+                    .col(
+                        ColumnDef::new(Submission::Description)
+                            .string()
+                            .not_null()
+                            .default("".to_string()),
+                    )
+                    .col(ColumnDef::new(Submission::Text).string())
+                    .col(ColumnDef::new(Submission::Data).binary())
+                    .col(ColumnDef::new(Submission::ThumbnailData).binary())
+                    .col(ColumnDef::new(Submission::AuthorHandle).string().not_null())
+                    .col(
+                        ColumnDef::new(Submission::AuthorInstance)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(Submission::IsNsfw)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(Submission::IsHidden)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(ColumnDef::new(Alias::new("legacy_literature_id")).integer())
+                    .col(ColumnDef::new(Alias::new("legacy_art_id")).integer())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Submission::Table, Submission::ContestId)
+                            .to(Contest::Table, Contest::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-submission-contest_id-category-author_handle-author_instance")
+                    .table(Submission::Table)
+                    .col(Submission::ContestId)
+                    .col(Submission::Category)
+                    .col(Submission::AuthorHandle)
+                    .col(Submission::AuthorInstance)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Vote::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Vote::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Vote::Handle).string().not_null())
+                    .col(ColumnDef::new(Vote::Instance).string().not_null())
+                    .col(ColumnDef::new(Vote::ContestId).integer().not_null())
+                    .col(ColumnDef::new(Vote::Category).string().not_null())
+                    .col(ColumnDef::new(Vote::SubmissionId).integer().not_null())
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(Vote::Handle)
+                            .col(Vote::Instance)
+                            .col(Vote::SubmissionId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Vote::Table, Vote::ContestId)
+                            .to(Contest::Table, Contest::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(Vote::Table, Vote::SubmissionId)
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-vote-contest_id-category-handle-instance")
+                    .table(Vote::Table)
+                    .col(Vote::ContestId)
+                    .col(Vote::Category)
+                    .col(Vote::Handle)
+                    .col(Vote::Instance)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Both categories move into the shared tables, keeping their relative order.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                INSERT INTO "submission" ("contest_id", "category", "title", "text", "author_handle", "author_instance", "is_nsfw", "is_hidden", "legacy_literature_id")
+                SELECT "contest_id", 'literature', "title", "text", "author_handle", "author_instance", "is_nsfw", "is_hidden", "id"
+                FROM "literature" ORDER BY "id";
+
+                INSERT INTO "submission" ("contest_id", "category", "title", "description", "data", "thumbnail_data", "author_handle", "author_instance", "is_nsfw", "is_hidden", "legacy_art_id")
+                SELECT "contest_id", 'art', "title", "description", "data", "thumbnail_data", "author_handle", "author_instance", "is_nsfw", "is_hidden", "id"
+                FROM "art" ORDER BY "id";
+
+                INSERT INTO "vote" ("handle", "instance", "contest_id", "category", "submission_id")
+                SELECT "v"."handle", "v"."instance", "v"."contest_id", 'literature', "s"."id"
+                FROM "literature_vote" "v" JOIN "submission" "s" ON "s"."legacy_literature_id" = "v"."literature_id"
+                ORDER BY "v"."id";
+
+                INSERT INTO "vote" ("handle", "instance", "contest_id", "category", "submission_id")
+                SELECT "v"."handle", "v"."instance", "v"."contest_id", 'art', "s"."id"
+                FROM "art_vote" "v" JOIN "submission" "s" ON "s"."legacy_art_id" = "v"."art_id"
+                ORDER BY "v"."id";
+
+                ALTER TABLE "submission"
+                    DROP COLUMN "legacy_literature_id",
+                    DROP COLUMN "legacy_art_id";
+
+                ALTER TABLE "contest" ADD COLUMN "categories" varchar[];
+                UPDATE "contest" SET "categories" = array_remove(ARRAY[
+                    CASE WHEN "literature_enabled" THEN 'literature' END,
+                    CASE WHEN "art_enabled" THEN 'art' END
+                ]::varchar[], NULL);
+                ALTER TABLE "contest"
+                    ALTER COLUMN "categories" SET NOT NULL,
+                    DROP COLUMN "literature_enabled",
+                    DROP COLUMN "art_enabled";
+                "#,
+            )
+            .await?;
+
+        for table in ["literature_vote", "art_vote", "literature", "art"] {
+            manager
+                .drop_table(Table::drop().table(Alias::new(table)).to_owned())
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Submissions of categories other than literature and art have nowhere to go and are lost.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                CREATE TABLE "literature" (
+                    "id" serial PRIMARY KEY,
+                    "title" varchar NOT NULL,
+                    "text" varchar NOT NULL,
+                    "author_handle" varchar NOT NULL,
+                    "author_instance" varchar NOT NULL,
+                    "is_nsfw" boolean NOT NULL DEFAULT false,
+                    "contest_id" integer NOT NULL
+                        REFERENCES "contest" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+                    "is_hidden" boolean NOT NULL DEFAULT false,
+                    "submission_id" integer
+                );
+                CREATE INDEX "idx-literature-contest_id-author_handle-author_instance"
+                    ON "literature" ("contest_id", "author_handle", "author_instance");
+                INSERT INTO "literature" ("title", "text", "author_handle", "author_instance", "is_nsfw", "contest_id", "is_hidden", "submission_id")
+                SELECT "title", COALESCE("text", ''), "author_handle", "author_instance", "is_nsfw", "contest_id", "is_hidden", "id"
+                FROM "submission" WHERE "category" = 'literature' ORDER BY "id";
+
+                CREATE TABLE "art" (
+                    "id" serial PRIMARY KEY,
+                    "title" varchar NOT NULL,
+                    "data" bytea NOT NULL,
+                    "thumbnail_data" bytea NOT NULL,
+                    "author_handle" varchar NOT NULL,
+                    "author_instance" varchar NOT NULL,
+                    "description" varchar NOT NULL DEFAULT '',
+                    "is_nsfw" boolean NOT NULL DEFAULT false,
+                    "contest_id" integer NOT NULL
+                        REFERENCES "contest" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+                    "is_hidden" boolean NOT NULL DEFAULT false,
+                    "submission_id" integer
+                );
+                CREATE INDEX "idx-art-contest_id-author_handle-author_instance"
+                    ON "art" ("contest_id", "author_handle", "author_instance");
+                INSERT INTO "art" ("title", "data", "thumbnail_data", "author_handle", "author_instance", "description", "is_nsfw", "contest_id", "is_hidden", "submission_id")
+                SELECT "title", COALESCE("data", ''), COALESCE("thumbnail_data", ''), "author_handle", "author_instance", "description", "is_nsfw", "contest_id", "is_hidden", "id"
+                FROM "submission" WHERE "category" = 'art' ORDER BY "id";
+
+                CREATE TABLE "literature_vote" (
+                    "id" serial PRIMARY KEY,
+                    "handle" varchar NOT NULL,
+                    "instance" varchar NOT NULL,
+                    "literature_id" integer NOT NULL
+                        REFERENCES "literature" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+                    "contest_id" integer NOT NULL
+                        REFERENCES "contest" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+                    UNIQUE ("handle", "instance", "literature_id")
+                );
+                INSERT INTO "literature_vote" ("handle", "instance", "literature_id", "contest_id")
+                SELECT "v"."handle", "v"."instance", "l"."id", "v"."contest_id"
+                FROM "vote" "v" JOIN "literature" "l" ON "l"."submission_id" = "v"."submission_id"
+                ORDER BY "v"."id";
+
+                CREATE TABLE "art_vote" (
+                    "id" serial PRIMARY KEY,
+                    "handle" varchar NOT NULL,
+                    "instance" varchar NOT NULL,
+                    "art_id" integer NOT NULL
+                        REFERENCES "art" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+                    "contest_id" integer NOT NULL
+                        REFERENCES "contest" ("id") ON DELETE CASCADE ON UPDATE CASCADE,
+                    UNIQUE ("handle", "instance", "art_id")
+                );
+                INSERT INTO "art_vote" ("handle", "instance", "art_id", "contest_id")
+                SELECT "v"."handle", "v"."instance", "a"."id", "v"."contest_id"
+                FROM "vote" "v" JOIN "art" "a" ON "a"."submission_id" = "v"."submission_id"
+                ORDER BY "v"."id";
+
+                ALTER TABLE "literature" DROP COLUMN "submission_id";
+                ALTER TABLE "art" DROP COLUMN "submission_id";
+
+                ALTER TABLE "contest"
+                    ADD COLUMN "literature_enabled" boolean NOT NULL DEFAULT true,
+                    ADD COLUMN "art_enabled" boolean NOT NULL DEFAULT true;
+                UPDATE "contest" SET
+                    "literature_enabled" = 'literature' = ANY("categories"),
+                    "art_enabled" = 'art' = ANY("categories");
+                ALTER TABLE "contest" DROP COLUMN "categories";
+                "#,
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Vote::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Submission::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum Submission {
+    Table,
+    Id,
+    ContestId,
+    Category,
+    Title,
+    Description,
+    Text,
+    Data,
+    ThumbnailData,
+    AuthorHandle,
+    AuthorInstance,
+    IsNsfw,
+    IsHidden,
+    TieBreakPriority,
+    DataKey,
+    ThumbnailKey,
+    MimeType,
+    DataHash,
+    ThumbnailHash,
+    PrivateDataKey,
+    AltTexts,
+    PerceptualHashes,
+    TextFormat,
+    AuthorAccountId,
+}
+
+#[derive(Iden)]
+pub enum Vote {
+    Table,
+    Id,
+    Handle,
+    Instance,
+    ContestId,
+    Category,
+    SubmissionId,
+    Score,
+    Rank,
+    AccountId,
+}
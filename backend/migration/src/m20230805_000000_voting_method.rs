@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{m20230801_000000_contest_table::Contest, m20230804_000000_submission_table::Vote};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Contest::VotingMethod)
+                            .string()
+                            .not_null()
+                            .default("approval"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Vote::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Vote::Score).integer())
+                    .add_column_if_not_exists(ColumnDef::new(Vote::Rank).integer())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .drop_column(Contest::VotingMethod)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Vote::Table)
+                    .drop_column(Vote::Score)
+                    .drop_column(Vote::Rank)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230801_000000_contest_table::Contest, m20230804_000000_submission_table::Submission,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Contest::TieBreak)
+                            .string()
+                            .not_null()
+                            .default("shared"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Submission::TieBreakPriority).integer(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .drop_column(Contest::TieBreak)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .drop_column(Submission::TieBreakPriority)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230801_000000_contest_table::Contest, m20230804_000000_submission_table::Submission,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Contest::JudgingOpenAt).timestamp_with_time_zone(),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Contest::JudgingCloseAt).timestamp_with_time_zone(),
+                    )
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Contest::JuryWeight)
+                            .double()
+                            .not_null()
+                            .default(0.0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(JuryScore::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(JuryScore::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(JuryScore::Handle).string().not_null())
+                    .col(ColumnDef::new(JuryScore::Instance).string().not_null())
+                    .col(ColumnDef::new(JuryScore::ContestId).integer().not_null())
+                    .col(ColumnDef::new(JuryScore::Category).string().not_null())
+                    .col(ColumnDef::new(JuryScore::SubmissionId).integer().not_null())
+                    .col(ColumnDef::new(JuryScore::Score).integer().not_null())
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(JuryScore::Handle)
+                            .col(JuryScore::Instance)
+                            .col(JuryScore::SubmissionId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(JuryScore::Table, JuryScore::ContestId)
+                            .to(Contest::Table, Contest::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(JuryScore::Table, JuryScore::SubmissionId)
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(JuryScore::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .drop_column(Contest::JudgingOpenAt)
+                    .drop_column(Contest::JudgingCloseAt)
+                    .drop_column(Contest::JuryWeight)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum JuryScore {
+    Table,
+    Id,
+    Handle,
+    Instance,
+    ContestId,
+    Category,
+    SubmissionId,
+    Score,
+    AccountId,
+}
@@ -0,0 +1,40 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230804_000000_submission_table::Submission;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Existing images stay in the binary columns until they are moved to the storage backend
+        // with the `migrate-storage` command.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Submission::DataKey).string())
+                    .add_column_if_not_exists(ColumnDef::new(Submission::ThumbnailKey).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Images already in the storage backend are not copied back and are lost.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .drop_column(Submission::DataKey)
+                    .drop_column(Submission::ThumbnailKey)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
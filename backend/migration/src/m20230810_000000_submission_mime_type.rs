@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230804_000000_submission_table::Submission;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Submission::MimeType).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Images still in the database are sniffed here, the ones already in the storage backend
+        // are left unknown.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                UPDATE "submission" SET "mime_type" = CASE
+                    WHEN substring("data" from 1 for 8) = '\x89504e470d0a1a0a'::bytea THEN 'image/png'
+                    WHEN substring("data" from 1 for 3) = '\xffd8ff'::bytea THEN 'image/jpeg'
+                    WHEN substring("data" from 1 for 4) = 'RIFF'::bytea
+                        AND substring("data" from 9 for 4) = 'WEBP'::bytea THEN 'image/webp'
+                    WHEN substring("data" from 1 for 6) IN ('GIF87a'::bytea, 'GIF89a'::bytea) THEN 'image/gif'
+                END
+                WHERE "data" IS NOT NULL
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .drop_column(Submission::MimeType)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
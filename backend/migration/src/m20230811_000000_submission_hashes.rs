@@ -0,0 +1,50 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230804_000000_submission_table::Submission;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Submission::DataHash).string())
+                    .add_column_if_not_exists(ColumnDef::new(Submission::ThumbnailHash).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Images already in the storage backend are hashed when they are served instead.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                UPDATE "submission" SET
+                    "data_hash" = encode(sha256("data"), 'hex'),
+                    "thumbnail_hash" = encode(sha256("thumbnail_data"), 'hex')
+                WHERE "data" IS NOT NULL OR "thumbnail_data" IS NOT NULL
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .drop_column(Submission::DataHash)
+                    .drop_column(Submission::ThumbnailHash)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
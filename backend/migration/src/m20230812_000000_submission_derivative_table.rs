@@ -0,0 +1,93 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230804_000000_submission_table::Submission;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Existing thumbnails are left in place. Derivatives of older images are generated when
+        // they are first requested.
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubmissionDerivative::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SubmissionDerivative::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionDerivative::SubmissionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionDerivative::Size)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionDerivative::Format)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionDerivative::Key)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionDerivative::Hash)
+                            .string()
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(SubmissionDerivative::SubmissionId)
+                            .col(SubmissionDerivative::Size)
+                            .col(SubmissionDerivative::Format),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                SubmissionDerivative::Table,
+                                SubmissionDerivative::SubmissionId,
+                            )
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SubmissionDerivative::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum SubmissionDerivative {
+    Table,
+    Id,
+    SubmissionId,
+    Size,
+    Format,
+    Key,
+    Hash,
+    Page,
+}
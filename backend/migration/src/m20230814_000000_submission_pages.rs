@@ -0,0 +1,149 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230804_000000_submission_table::Submission,
+    m20230812_000000_submission_derivative_table::SubmissionDerivative,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Existing images become the first and only page, without alt text.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                ALTER TABLE "submission" ADD COLUMN IF NOT EXISTS "alt_texts" varchar[] NOT NULL DEFAULT '{}';
+                UPDATE "submission" SET "alt_texts" = '{""}'
+                WHERE "data_key" IS NOT NULL OR "data" IS NOT NULL;
+                "#,
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubmissionPage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SubmissionPage::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionPage::SubmissionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SubmissionPage::Page).integer().not_null())
+                    .col(ColumnDef::new(SubmissionPage::DataKey).string().not_null())
+                    .col(ColumnDef::new(SubmissionPage::MimeType).string().not_null())
+                    .col(ColumnDef::new(SubmissionPage::DataHash).string().not_null())
+                    .col(ColumnDef::new(SubmissionPage::PrivateDataKey).string())
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(SubmissionPage::SubmissionId)
+                            .col(SubmissionPage::Page),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SubmissionPage::Table, SubmissionPage::SubmissionId)
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(SubmissionDerivative::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(SubmissionDerivative::Page)
+                            .integer()
+                            .not_null()
+                            .default(1),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"ALTER TABLE "submission_derivative" DROP CONSTRAINT IF EXISTS "submission_derivative_submission_id_size_format_key""#,
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-submission_derivative-submission_id-page-size-format")
+                    .table(SubmissionDerivative::Table)
+                    .col(SubmissionDerivative::SubmissionId)
+                    .col(SubmissionDerivative::Page)
+                    .col(SubmissionDerivative::Size)
+                    .col(SubmissionDerivative::Format)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-submission_derivative-submission_id-page-size-format")
+                    .table(SubmissionDerivative::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                DELETE FROM "submission_derivative" WHERE "page" <> 1;
+                ALTER TABLE "submission_derivative"
+                    DROP COLUMN "page",
+                    ADD CONSTRAINT "submission_derivative_submission_id_size_format_key" UNIQUE ("submission_id", "size", "format");
+                "#,
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(SubmissionPage::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .drop_column(Submission::AltTexts)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+pub enum SubmissionPage {
+    Table,
+    Id,
+    SubmissionId,
+    Page,
+    DataKey,
+    MimeType,
+    DataHash,
+    PrivateDataKey,
+}
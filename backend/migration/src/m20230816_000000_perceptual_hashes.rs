@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230804_000000_submission_table::Submission;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Existing images are hashed by jobs, as decoding them takes too long for a migration.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                ALTER TABLE "submission" ADD COLUMN IF NOT EXISTS "perceptual_hashes" bigint[] NOT NULL DEFAULT '{}';
+                INSERT INTO "job" ("kind", "payload")
+                SELECT 'hashImages', jsonb_build_object('kind', 'hashImages', 'submissionId', "id")
+                FROM "submission" WHERE cardinality("alt_texts") > 0;
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(r#"DELETE FROM "job" WHERE "kind" = 'hashImages'"#)
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .drop_column(Submission::PerceptualHashes)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
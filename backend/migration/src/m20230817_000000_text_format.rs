@@ -0,0 +1,43 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230804_000000_submission_table::Submission;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Submission::TextFormat).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing texts were all submitted as plain text.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"UPDATE "submission" SET "text_format" = 'plain' WHERE "text" IS NOT NULL"#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .drop_column(Submission::TextFormat)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
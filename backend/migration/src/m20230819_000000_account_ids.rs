@@ -0,0 +1,233 @@
+use sea_orm_migration::prelude::*;
+
+use crate::{
+    m20230804_000000_submission_table::{Submission, Vote},
+    m20230808_000000_jury_score_table::JuryScore,
+    m20230818_000000_session_table::Session,
+};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Existing rows are claimed by their accounts on the next login, as the IDs cannot be
+        // known before. Sessions have no IDs either, so everyone logs in again.
+        manager
+            .get_connection()
+            .execute_unprepared(r#"DELETE FROM "session""#)
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Session::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Session::AccountId).string().not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Submission::AuthorAccountId).string())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Vote::Table)
+                    .add_column_if_not_exists(ColumnDef::new(Vote::AccountId).string())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JuryScore::Table)
+                    .add_column_if_not_exists(ColumnDef::new(JuryScore::AccountId).string())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Usernames can be taken by another account after a rename, so they are not unique
+        // anymore.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                ALTER TABLE "vote" DROP CONSTRAINT IF EXISTS "vote_handle_instance_submission_id_key";
+                ALTER TABLE "jury_score" DROP CONSTRAINT IF EXISTS "jury_score_handle_instance_submission_id_key";
+                "#,
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-submission-contest_id-category-author_handle-author_instance")
+                    .table(Submission::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-vote-contest_id-category-handle-instance")
+                    .table(Vote::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-submission-contest_id-category-author_account_id")
+                    .table(Submission::Table)
+                    .col(Submission::ContestId)
+                    .col(Submission::Category)
+                    .col(Submission::AuthorAccountId)
+                    .col(Submission::AuthorInstance)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-vote-contest_id-category-account_id-instance")
+                    .table(Vote::Table)
+                    .col(Vote::ContestId)
+                    .col(Vote::Category)
+                    .col(Vote::AccountId)
+                    .col(Vote::Instance)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-vote-account_id-instance-submission_id")
+                    .table(Vote::Table)
+                    .col(Vote::AccountId)
+                    .col(Vote::Instance)
+                    .col(Vote::SubmissionId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-jury_score-account_id-instance-submission_id")
+                    .table(JuryScore::Table)
+                    .col(JuryScore::AccountId)
+                    .col(JuryScore::Instance)
+                    .col(JuryScore::SubmissionId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (name, table) in [
+            (
+                "idx-submission-contest_id-category-author_account_id",
+                Submission::Table.into_iden(),
+            ),
+            (
+                "idx-vote-contest_id-category-account_id-instance",
+                Vote::Table.into_iden(),
+            ),
+            (
+                "idx-vote-account_id-instance-submission_id",
+                Vote::Table.into_iden(),
+            ),
+            (
+                "idx-jury_score-account_id-instance-submission_id",
+                JuryScore::Table.into_iden(),
+            ),
+        ] {
+            manager
+                .drop_index(Index::drop().name(name).table(table).to_owned())
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-submission-contest_id-category-author_handle-author_instance")
+                    .table(Submission::Table)
+                    .col(Submission::ContestId)
+                    .col(Submission::Category)
+                    .col(Submission::AuthorHandle)
+                    .col(Submission::AuthorInstance)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-vote-contest_id-category-handle-instance")
+                    .table(Vote::Table)
+                    .col(Vote::ContestId)
+                    .col(Vote::Category)
+                    .col(Vote::Handle)
+                    .col(Vote::Instance)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                ALTER TABLE "vote" ADD CONSTRAINT "vote_handle_instance_submission_id_key" UNIQUE ("handle", "instance", "submission_id");
+                ALTER TABLE "jury_score" ADD CONSTRAINT "jury_score_handle_instance_submission_id_key" UNIQUE ("handle", "instance", "submission_id");
+                "#,
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(JuryScore::Table)
+                    .drop_column(JuryScore::AccountId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Vote::Table)
+                    .drop_column(Vote::AccountId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submission::Table)
+                    .drop_column(Submission::AuthorAccountId)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Session::Table)
+                    .drop_column(Session::AccountId)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230804_000000_submission_table::Submission;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SubmissionDuplicate::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SubmissionDuplicate::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionDuplicate::SubmissionId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionDuplicate::Page)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionDuplicate::OriginalId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionDuplicate::OriginalPage)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SubmissionDuplicate::Distance)
+                            .integer()
+                            .not_null(),
+                    )
+                    .index(
+                        Index::create()
+                            .unique()
+                            .col(SubmissionDuplicate::SubmissionId)
+                            .col(SubmissionDuplicate::Page)
+                            .col(SubmissionDuplicate::OriginalId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(
+                                SubmissionDuplicate::Table,
+                                SubmissionDuplicate::SubmissionId,
+                            )
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .from(SubmissionDuplicate::Table, SubmissionDuplicate::OriginalId)
+                            .to(Submission::Table, Submission::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Rows are looked up by either side when a submission is hashed again.
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-submission_duplicate-original_id")
+                    .table(SubmissionDuplicate::Table)
+                    .col(SubmissionDuplicate::OriginalId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Duplicates are detected when images are hashed, so existing images are hashed again.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                INSERT INTO "job" ("kind", "payload")
+                SELECT 'hashImages', jsonb_build_object('kind', 'hashImages', 'submissionId', "id")
+                FROM "submission" WHERE cardinality("perceptual_hashes") > 0;
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SubmissionDuplicate::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum SubmissionDuplicate {
+    Table,
+    Id,
+    SubmissionId,
+    Page,
+    OriginalId,
+    OriginalPage,
+    Distance,
+}
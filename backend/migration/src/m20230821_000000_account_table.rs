@@ -0,0 +1,127 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20230818_000000_session_table::Session;
+
+/// Records every account that has logged in, so rows recorded before account IDs are only
+/// claimed by handle on the first login of an account.
+///
+/// An account that already logged in never claims rows by handle again, so it cannot take over
+/// the rows of an account that had its handle before a rename. The risk remains for the first
+/// login of an account: if the author of legacy rows renamed themselves without logging in since
+/// account IDs were introduced, the next new account holding their old handle claims the rows.
+/// Such rows can be reassigned by updating their account ID in the database.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Account::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Account::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Account::AccountId).string().not_null())
+                    .col(ColumnDef::new(Account::Instance).string().not_null())
+                    .col(ColumnDef::new(Account::Handle).string().not_null())
+                    .col(
+                        ColumnDef::new(Account::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .index(
+                        Index::create()
+                            .col(Account::AccountId)
+                            .col(Account::Instance)
+                            .unique(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Accounts that logged in since account IDs were introduced have claimed their rows
+        // already.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                INSERT INTO "account" ("account_id", "instance", "handle")
+                SELECT "account_id", "instance", "handle" FROM "session"
+                UNION SELECT "author_account_id", "author_instance", "author_handle" FROM "submission" WHERE "author_account_id" IS NOT NULL
+                UNION SELECT "account_id", "instance", "handle" FROM "vote" WHERE "account_id" IS NOT NULL
+                UNION SELECT "account_id", "instance", "handle" FROM "jury_score" WHERE "account_id" IS NOT NULL
+                ON CONFLICT DO NOTHING
+                "#,
+            )
+            .await?;
+
+        // Sessions are revoked by account.
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-session-handle-instance")
+                    .table(Session::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name("idx-session-account_id-instance")
+                    .table(Session::Table)
+                    .col(Session::AccountId)
+                    .col(Session::Instance)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-session-account_id-instance")
+                    .table(Session::Table)
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-session-handle-instance")
+                    .table(Session::Table)
+                    .col(Session::Handle)
+                    .col(Session::Instance)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Account::Table).to_owned())
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+#[allow(clippy::enum_variant_names)]
+enum Account {
+    Table,
+    Id,
+    AccountId,
+    Instance,
+    Handle,
+    CreatedAt,
+}
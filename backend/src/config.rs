@@ -1,12 +1,13 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
-use jsonwebtoken::{DecodingKey, EncodingKey};
 use once_cell::sync::Lazy;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use url::Url;
 
+use crate::entity::contest::{TieBreak, VotingMethod};
+
 pub static CONFIG: Lazy<Config> =
     Lazy::new(|| Config::try_from_env().expect("failed to parse config from env vars"));
 
@@ -14,6 +15,14 @@ fn default_listen_addr() -> String {
     "0.0.0.0:3000".to_string()
 }
 
+fn default_site_name() -> String {
+    env!("CARGO_PKG_NAME").to_string()
+}
+
+fn default_contest_slug() -> String {
+    "default".to_string()
+}
+
 fn default_database_host() -> String {
     "localhost".to_string()
 }
@@ -38,28 +47,98 @@ fn default_static_files_directory_path() -> PathBuf {
     PathBuf::from("../frontend/dist")
 }
 
-fn default_literature_enabled() -> bool {
-    true
+fn default_storage_path() -> PathBuf {
+    PathBuf::from("data")
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+fn default_title_max_length() -> usize {
+    100
+}
+
+fn default_literature_text_max_length() -> usize {
+    7000
 }
 
-fn default_art_enabled() -> bool {
-    true
+fn default_art_description_max_length() -> usize {
+    2000
 }
 
-fn deserialize_jwt_secret<'de, D>(d: D) -> Result<(EncodingKey, DecodingKey), D::Error>
+fn default_art_image_max_size() -> usize {
+    1024 * 1024 * 50
+}
+
+fn default_art_image_max_dimension() -> u32 {
+    10000
+}
+
+fn default_image_sizes() -> Vec<ImageSize> {
+    vec![
+        ImageSize {
+            name: "small".to_string(),
+            max_width: 256,
+            max_height: 256,
+        },
+        ImageSize {
+            name: "medium".to_string(),
+            max_width: 800,
+            max_height: 800,
+        },
+        ImageSize {
+            name: "display".to_string(),
+            max_width: 1920,
+            max_height: 1920,
+        },
+    ]
+}
+
+fn default_alt_text_max_length() -> usize {
+    1000
+}
+
+fn default_page_limit() -> u64 {
+    1
+}
+
+fn default_duplicate_max_distance() -> u32 {
+    10
+}
+
+fn default_session_lifetime_secs() -> i64 {
+    60 * 60 * 24
+}
+
+fn default_vote_limit() -> u64 {
+    5
+}
+
+fn default_submission_limit() -> u64 {
+    1
+}
+
+fn deserialize_categories<'de, D>(d: D) -> Result<Vec<Category>, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
     let s = std::borrow::Cow::<'_, str>::deserialize(d)?;
-    Ok((
-        EncodingKey::from_secret(s.as_bytes()),
-        DecodingKey::from_secret(s.as_bytes()),
-    ))
+    serde_json::from_str(&s).map_err(serde::de::Error::custom)
+}
+
+fn deserialize_image_sizes<'de, D>(d: D) -> Result<Vec<ImageSize>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let s = std::borrow::Cow::<'_, str>::deserialize(d)?;
+    serde_json::from_str(&s).map_err(serde::de::Error::custom)
 }
 
 #[derive(Clone, Deserialize)]
 pub struct Config {
-    pub contest_name: String,
+    #[serde(default = "default_site_name")]
+    pub site_name: String,
 
     #[serde(default = "default_listen_addr")]
     pub listen_addr: String,
@@ -79,22 +158,126 @@ pub struct Config {
     #[serde(default = "default_static_files_directory_path")]
     pub static_files_directory_path: PathBuf,
 
-    #[serde(deserialize_with = "deserialize_jwt_secret")]
-    pub jwt_secret: (EncodingKey, DecodingKey),
+    #[serde(default)]
+    pub storage_backend: StorageBackend,
+    /// Directory of the local storage backend.
+    #[serde(default = "default_storage_path")]
+    pub storage_path: PathBuf,
+    /// S3-compatible endpoint, accessed with path-style URLs such as `{endpoint}/{bucket}/{key}`.
+    #[serde(default)]
+    pub s3_endpoint: Option<Url>,
+    #[serde(default)]
+    pub s3_bucket: Option<String>,
+    #[serde(default = "default_s3_region")]
+    pub s3_region: String,
+    #[serde(default)]
+    pub s3_access_key: Option<String>,
+    #[serde(default)]
+    pub s3_secret_key: Option<String>,
+
+    /// How long a login lasts without using the site. Sessions used in their second half are
+    /// extended by this much again.
+    #[serde(default = "default_session_lifetime_secs")]
+    pub session_lifetime_secs: i64,
 
-    #[serde(default = "default_literature_enabled")]
-    pub literature_enabled: bool,
-    #[serde(default = "default_art_enabled")]
-    pub art_enabled: bool,
+    /// Contest which is created or updated on startup. Other contests in the database are left
+    /// untouched, so past contests stay browsable.
+    #[serde(default = "default_contest_slug")]
+    pub contest_slug: String,
+    #[serde(default)]
+    pub contest_name: Option<String>,
 
-    #[serde(with = "time::serde::rfc3339")]
-    pub submission_open_at: OffsetDateTime,
-    #[serde(with = "time::serde::rfc3339")]
-    pub submission_close_at: OffsetDateTime,
-    #[serde(with = "time::serde::rfc3339")]
-    pub voting_open_at: OffsetDateTime,
-    #[serde(with = "time::serde::rfc3339")]
-    pub voting_close_at: OffsetDateTime,
+    /// Categories enabled in the configured contest, all categories if not set.
+    #[serde(default)]
+    pub contest_categories: Option<Vec<String>>,
+
+    #[serde(default)]
+    pub voting_method: VotingMethod,
+    #[serde(default)]
+    pub tie_break: TieBreak,
+    /// Hide authors from voters until voting closes.
+    #[serde(default)]
+    pub blind: bool,
+
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub submission_open_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub submission_close_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub voting_open_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub voting_close_at: Option<OffsetDateTime>,
+    /// Optional judging window for the jury, which may outlast voting.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub judging_open_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub judging_close_at: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub jury_weight: f64,
+
+    /// Categories as a JSON array. If not set, `literature` and `art` are built from the
+    /// `LITERATURE_*` and `ART_*` limits below.
+    #[serde(default, deserialize_with = "deserialize_categories")]
+    pub categories: Vec<Category>,
+
+    #[serde(default = "default_title_max_length")]
+    pub literature_title_max_length: usize,
+    #[serde(default = "default_literature_text_max_length")]
+    pub literature_text_max_length: usize,
+    #[serde(default = "default_vote_limit")]
+    pub literature_vote_limit: u64,
+    #[serde(default = "default_submission_limit")]
+    pub literature_submission_limit: u64,
+
+    #[serde(default = "default_title_max_length")]
+    pub art_title_max_length: usize,
+    #[serde(default = "default_art_description_max_length")]
+    pub art_description_max_length: usize,
+    #[serde(default = "default_art_image_max_size")]
+    pub art_image_max_size: usize,
+    #[serde(default = "default_art_image_max_dimension")]
+    pub art_image_max_width: u32,
+    #[serde(default = "default_art_image_max_dimension")]
+    pub art_image_max_height: u32,
+    #[serde(default = "default_alt_text_max_length")]
+    pub art_alt_text_max_length: usize,
+    #[serde(default = "default_page_limit")]
+    pub art_page_limit: u64,
+    #[serde(default = "default_vote_limit")]
+    pub art_vote_limit: u64,
+    #[serde(default = "default_submission_limit")]
+    pub art_submission_limit: u64,
+
+    /// Keep a private copy of every uploaded image as is, before its metadata is stripped.
+    #[serde(default)]
+    pub keep_private_originals: bool,
+    /// Sizes of the resized images generated for image submissions, as a JSON array. The first
+    /// one is served as the thumbnail by default.
+    #[serde(
+        default = "default_image_sizes",
+        deserialize_with = "deserialize_image_sizes"
+    )]
+    pub image_sizes: Vec<ImageSize>,
+    /// Images whose perceptual hashes differ in at most this many of their 64 bits are listed as
+    /// possible duplicates. Raising it only applies to images hashed afterwards.
+    #[serde(default = "default_duplicate_max_distance")]
+    pub duplicate_max_distance: u32,
+
+    /// Accounts as `account_id@instance`, with the ID shown as `accountId` by `/api/user`, so
+    /// roles do not pass on to whoever takes a handle after a rename.
+    #[serde(default)]
+    pub admin_accounts: Vec<String>,
+    #[serde(default)]
+    pub moderator_accounts: Vec<String>,
+    #[serde(default)]
+    pub judge_accounts: Vec<String>,
+    /// Former `handle@instance` roles, only read to refuse starting with them.
+    #[serde(default)]
+    admins: Vec<String>,
+    #[serde(default)]
+    moderators: Vec<String>,
+    #[serde(default)]
+    judges: Vec<String>,
 
     #[serde(default)]
     pub misskey_base_url: Option<Url>,
@@ -102,9 +285,216 @@ pub struct Config {
     pub misskey_api_key: Option<String>,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StorageBackend {
+    /// Files under `STORAGE_PATH`.
+    #[default]
+    Local,
+    /// Objects in an S3-compatible bucket.
+    S3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CategoryKind {
+    /// Text body submitted as JSON.
+    Text,
+    /// PNG, JPEG, WebP or GIF images submitted as multipart form data, with generated derivatives
+    /// in every configured image size.
+    Image,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Category {
+    pub slug: String,
+    pub name: String,
+    pub kind: CategoryKind,
+    #[serde(default = "default_title_max_length")]
+    pub title_max_length: usize,
+    #[serde(default = "default_art_description_max_length")]
+    pub description_max_length: usize,
+    #[serde(default = "default_literature_text_max_length")]
+    pub text_max_length: usize,
+    #[serde(default = "default_art_image_max_size")]
+    pub image_max_size: usize,
+    #[serde(default = "default_art_image_max_dimension")]
+    pub image_max_width: u32,
+    #[serde(default = "default_art_image_max_dimension")]
+    pub image_max_height: u32,
+    #[serde(default = "default_alt_text_max_length")]
+    pub alt_text_max_length: usize,
+    /// Number of images a submission may have, such as the pages of a comic.
+    #[serde(default = "default_page_limit")]
+    pub page_limit: u64,
+    #[serde(default = "default_vote_limit")]
+    pub vote_limit: u64,
+    #[serde(default = "default_submission_limit")]
+    pub submission_limit: u64,
+}
+
+/// Bounding box an image derivative is scaled down to fit in, keeping its aspect ratio.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageSize {
+    pub name: String,
+    pub max_width: u32,
+    pub max_height: u32,
+}
+
+/// Slugs which would collide with other routes under `/api/contest/:slug`.
+const RESERVED_CATEGORY_SLUGS: &[&str] = &[
+    "name",
+    "enabled",
+    "rules",
+    "submission",
+    "voting",
+    "judging",
+    "result",
+];
+
 impl Config {
     pub fn try_from_env() -> Result<Self> {
-        let config: Config = envy::from_env()?;
+        let mut config: Config = envy::from_env()?;
+
+        if config.categories.is_empty() {
+            config.categories = vec![
+                Category {
+                    slug: "literature".to_string(),
+                    name: "글".to_string(),
+                    kind: CategoryKind::Text,
+                    title_max_length: config.literature_title_max_length,
+                    description_max_length: 0,
+                    text_max_length: config.literature_text_max_length,
+                    image_max_size: 0,
+                    image_max_width: 0,
+                    image_max_height: 0,
+                    alt_text_max_length: 0,
+                    page_limit: 0,
+                    vote_limit: config.literature_vote_limit,
+                    submission_limit: config.literature_submission_limit,
+                },
+                Category {
+                    slug: "art".to_string(),
+                    name: "그림".to_string(),
+                    kind: CategoryKind::Image,
+                    title_max_length: config.art_title_max_length,
+                    description_max_length: config.art_description_max_length,
+                    text_max_length: 0,
+                    image_max_size: config.art_image_max_size,
+                    image_max_width: config.art_image_max_width,
+                    image_max_height: config.art_image_max_height,
+                    alt_text_max_length: config.art_alt_text_max_length,
+                    page_limit: config.art_page_limit,
+                    vote_limit: config.art_vote_limit,
+                    submission_limit: config.art_submission_limit,
+                },
+            ];
+        }
+
+        for (i, category) in config.categories.iter().enumerate() {
+            if category.slug.is_empty()
+                || !category
+                    .slug
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+            {
+                anyhow::bail!("invalid category slug: {:?}", category.slug);
+            }
+            if RESERVED_CATEGORY_SLUGS.contains(&category.slug.as_str()) {
+                anyhow::bail!("reserved category slug: {}", category.slug);
+            }
+            if category.kind == CategoryKind::Image && category.page_limit == 0 {
+                anyhow::bail!("zero page limit: {}", category.slug);
+            }
+            if config.categories[..i]
+                .iter()
+                .any(|other| other.slug == category.slug)
+            {
+                anyhow::bail!("duplicated category slug: {}", category.slug);
+            }
+        }
+
+        if config.image_sizes.is_empty() {
+            anyhow::bail!("at least one image size is required");
+        }
+        for (i, size) in config.image_sizes.iter().enumerate() {
+            // Size names end up in storage keys, which only allow lowercase letters and digits.
+            if size.name.is_empty()
+                || !size
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+            {
+                anyhow::bail!("invalid image size name: {:?}", size.name);
+            }
+            if size.max_width == 0 || size.max_height == 0 {
+                anyhow::bail!("empty image size: {}", size.name);
+            }
+            if config.image_sizes[..i]
+                .iter()
+                .any(|other| other.name == size.name)
+            {
+                anyhow::bail!("duplicated image size name: {}", size.name);
+            }
+        }
+
+        if config.storage_backend == StorageBackend::S3
+            && (config.s3_endpoint.is_none()
+                || config.s3_bucket.is_none()
+                || config.s3_access_key.is_none()
+                || config.s3_secret_key.is_none())
+        {
+            anyhow::bail!(
+                "S3 storage backend requires endpoint, bucket, access key and secret key"
+            );
+        }
+
+        if !config.admins.is_empty() || !config.moderators.is_empty() || !config.judges.is_empty() {
+            anyhow::bail!(
+                "ADMINS, MODERATORS and JUDGES are replaced by ADMIN_ACCOUNTS, MODERATOR_ACCOUNTS \
+                 and JUDGE_ACCOUNTS, which take account_id@instance"
+            );
+        }
+
+        if config.session_lifetime_secs <= 0 {
+            anyhow::bail!("session lifetime must be positive");
+        }
+
+        if !(0.0..=1.0).contains(&config.jury_weight) {
+            anyhow::bail!("jury weight must be between 0 and 1");
+        }
+
+        if let Some(contest_categories) = &config.contest_categories {
+            if let Some(slug) = contest_categories
+                .iter()
+                .find(|slug| config.category(slug).is_none())
+            {
+                anyhow::bail!("unknown contest category: {}", slug);
+            }
+        }
+
         Ok(config)
     }
+
+    pub fn category(&self, slug: &str) -> Option<&Category> {
+        self.categories
+            .iter()
+            .find(|category| category.slug == slug)
+    }
+
+    pub fn image_size(&self, name: &str) -> Option<&ImageSize> {
+        self.image_sizes.iter().find(|size| size.name == name)
+    }
+
+    /// Largest request body any category accepts.
+    pub fn max_upload_size(&self) -> usize {
+        self.categories
+            .iter()
+            .filter(|category| category.kind == CategoryKind::Image)
+            .map(|category| category.image_max_size * category.page_limit as usize)
+            .max()
+            .unwrap_or(0)
+    }
 }
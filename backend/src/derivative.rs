@@ -0,0 +1,468 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use axum::body::Bytes;
+use sea_orm::{
+    sea_query::{Expr, OnConflict},
+    ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, Iterable,
+    QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+
+use crate::{
+    config::{ImageSize, CONFIG},
+    entity::{
+        submission,
+        submission_derivative::{self, DerivativeFormat},
+        submission_duplicate, submission_page,
+    },
+    media::{self, ImageFormat},
+    storage::{self, Storage},
+};
+
+/// Derivative generated for a page and put to the storage backend.
+struct Derivative {
+    size: &'static ImageSize,
+    format: DerivativeFormat,
+    key: String,
+    hash: String,
+}
+
+/// Generates the derivatives of a page in the sizes and formats, and records them unless the
+/// image was replaced or they were recorded by another job first. Pages without an image are
+/// skipped.
+async fn generate(
+    db: &DatabaseConnection,
+    storage: &dyn Storage,
+    submission_id: i32,
+    page: i32,
+    targets: Vec<(&'static ImageSize, DerivativeFormat)>,
+) -> Result<()> {
+    let Some((original_key, data)) = load_original(db, storage, submission_id, page).await? else {
+        return Ok(());
+    };
+
+    let rendered = tokio::task::spawn_blocking(move || render(&data, targets))
+        .await
+        .context("failed to join image task")??;
+    let mut derivatives = Vec::with_capacity(rendered.len());
+    for (size, format, data) in rendered {
+        let key = storage::new_image_key(&format!("{}/{}", size.name, format.extension()));
+        let hash = storage::content_hash(&data);
+        if let Err(err) = storage.put(&key, data.into()).await {
+            discard(storage, &derivatives).await;
+            return Err(err).context("failed to put derivative to storage");
+        }
+        derivatives.push(Derivative {
+            size,
+            format,
+            key,
+            hash,
+        });
+    }
+
+    match record(db, submission_id, page, original_key, &derivatives).await {
+        Ok(unrecorded) => {
+            discard(storage, unrecorded).await;
+            Ok(())
+        }
+        Err(err) => {
+            discard(storage, &derivatives).await;
+            Err(err)
+        }
+    }
+}
+
+/// Generates the derivatives missing for every page of the submission, such as for new uploads
+/// or newly configured sizes.
+pub async fn generate_missing(
+    db: &DatabaseConnection,
+    storage: &dyn Storage,
+    submission_id: i32,
+) -> Result<()> {
+    let Some(alt_texts) = submission::Entity::find_by_id(submission_id)
+        .select_only()
+        .column(submission::Column::AltTexts)
+        .into_tuple::<Vec<String>>()
+        .one(db)
+        .await
+        .context("failed to query database")?
+    else {
+        return Ok(());
+    };
+    let existing = submission_derivative::Entity::find()
+        .filter(submission_derivative::Column::SubmissionId.eq(submission_id))
+        .select_only()
+        .column(submission_derivative::Column::Page)
+        .column(submission_derivative::Column::Size)
+        .column(submission_derivative::Column::Format)
+        .into_tuple::<(i32, String, DerivativeFormat)>()
+        .all(db)
+        .await
+        .context("failed to query database")?
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    for page in (1..).take(alt_texts.len()) {
+        let targets = CONFIG
+            .image_sizes
+            .iter()
+            .flat_map(|size| DerivativeFormat::iter().map(move |format| (size, format)))
+            .filter(|(size, format)| !existing.contains(&(page, size.name.clone(), *format)))
+            .collect::<Vec<_>>();
+        if !targets.is_empty() {
+            generate(db, storage, submission_id, page, targets).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Computes the perceptual hashes of all pages of the submission, and records which of its images
+/// resemble images of other submissions, unless the images were replaced in the meantime.
+pub async fn hash_images(
+    db: &DatabaseConnection,
+    storage: &dyn Storage,
+    submission_id: i32,
+) -> Result<()> {
+    let Some(alt_texts) = submission::Entity::find_by_id(submission_id)
+        .select_only()
+        .column(submission::Column::AltTexts)
+        .into_tuple::<Vec<String>>()
+        .one(db)
+        .await
+        .context("failed to query database")?
+    else {
+        return Ok(());
+    };
+
+    let mut keys = Vec::with_capacity(alt_texts.len());
+    let mut hashes = Vec::with_capacity(alt_texts.len());
+    for page in (1..).take(alt_texts.len()) {
+        let Some((key, data)) = load_original(db, storage, submission_id, page).await? else {
+            // Replaced by fewer images.
+            return Ok(());
+        };
+        let hash = tokio::task::spawn_blocking(move || -> Result<u64> {
+            let image_format = ImageFormat::sniff(&data).context("unknown image format")?;
+            Ok(media::perceptual_hash(&media::decode_image(
+                &data,
+                image_format,
+            )?))
+        })
+        .await
+        .context("failed to join image task")??;
+        keys.push(key);
+        hashes.push(hash as i64);
+    }
+
+    let tx = db.begin().await.context("failed to begin transaction")?;
+
+    // Locking the submission waits for updates replacing its images to finish.
+    let Some(key) = submission::Entity::find_by_id(submission_id)
+        .select_only()
+        .column(submission::Column::DataKey)
+        .lock_exclusive()
+        .into_tuple::<Option<String>>()
+        .one(&tx)
+        .await
+        .context("failed to query database")?
+    else {
+        return Ok(());
+    };
+    let page_keys = submission_page::Entity::find()
+        .filter(submission_page::Column::SubmissionId.eq(submission_id))
+        .order_by_asc(submission_page::Column::Page)
+        .select_only()
+        .column(submission_page::Column::DataKey)
+        .into_tuple::<String>()
+        .all(&tx)
+        .await
+        .context("failed to query database")?;
+    if std::iter::once(key)
+        .chain(page_keys.into_iter().map(Some))
+        .ne(keys)
+    {
+        // The job enqueued with the new images hashes them.
+        return Ok(());
+    }
+
+    submission::Entity::update_many()
+        .col_expr(
+            submission::Column::PerceptualHashes,
+            Expr::value(hashes.clone()),
+        )
+        .filter(submission::Column::Id.eq(submission_id))
+        .exec(&tx)
+        .await
+        .context("failed to update database")?;
+
+    // Submissions are compared one at a time, so that two hashed at once are compared with each
+    // other by whichever comes second.
+    tx.execute_unprepared("SELECT pg_advisory_xact_lock(hashtext('submission_duplicate'))")
+        .await
+        .context("failed to lock duplicates")?;
+    submission_duplicate::Entity::delete_many()
+        .filter(
+            submission_duplicate::Column::SubmissionId
+                .eq(submission_id)
+                .or(submission_duplicate::Column::OriginalId.eq(submission_id)),
+        )
+        .exec(&tx)
+        .await
+        .context("failed to delete from database")?;
+
+    let others = submission::Entity::find()
+        .select_only()
+        .column(submission::Column::Id)
+        .column(submission::Column::PerceptualHashes)
+        .filter(submission::Column::Id.ne(submission_id))
+        .filter(Expr::cust(r#"cardinality("perceptual_hashes") > 0"#))
+        .into_tuple::<(i32, Vec<i64>)>()
+        .all(&tx)
+        .await
+        .context("failed to query database")?;
+    let mut duplicates = Vec::new();
+    for (other_id, other_hashes) in others {
+        // The earlier submission is the original.
+        let (submission_id, hashes, original_id, original_hashes) = if other_id < submission_id {
+            (submission_id, &hashes, other_id, &other_hashes)
+        } else {
+            (other_id, &other_hashes, submission_id, &hashes)
+        };
+        duplicates.extend(
+            duplicate_pages(hashes, original_hashes, CONFIG.duplicate_max_distance).map(
+                |(page, original_page, distance)| submission_duplicate::ActiveModel {
+                    id: ActiveValue::NotSet,
+                    submission_id: ActiveValue::Set(submission_id),
+                    page: ActiveValue::Set(page),
+                    original_id: ActiveValue::Set(original_id),
+                    original_page: ActiveValue::Set(original_page),
+                    distance: ActiveValue::Set(distance as i32),
+                },
+            ),
+        );
+    }
+    if !duplicates.is_empty() {
+        submission_duplicate::Entity::insert_many(duplicates)
+            .exec(&tx)
+            .await
+            .context("failed to insert to database")?;
+    }
+
+    tx.commit().await.context("failed to commit to database")?;
+
+    Ok(())
+}
+
+/// Pages resembling a page of the original, as `(page, original_page, distance)` with the closest
+/// page of the original, given the perceptual hashes of their pages.
+fn duplicate_pages<'a>(
+    hashes: &'a [i64],
+    original_hashes: &'a [i64],
+    max_distance: u32,
+) -> impl Iterator<Item = (i32, i32, u32)> + 'a {
+    // Flat images all hash to 0 and would match each other without being copies.
+    fn pages(hashes: &[i64]) -> impl Iterator<Item = (i32, u64)> + '_ {
+        (1..)
+            .zip(hashes.iter().map(|&hash| hash as u64))
+            .filter(|&(_, hash)| hash != 0)
+    }
+    pages(hashes).filter_map(move |(page, hash)| {
+        pages(original_hashes)
+            .map(|(original_page, original_hash)| {
+                (
+                    media::perceptual_hash_distance(hash, original_hash),
+                    original_page,
+                )
+            })
+            .min()
+            .filter(|&(distance, _)| distance <= max_distance)
+            .map(|(distance, original_page)| (page, original_page, distance))
+    })
+}
+
+/// Reads the original image of a page with its storage key. Images submitted before the storage
+/// backend existed have no key.
+async fn load_original(
+    db: &DatabaseConnection,
+    storage: &dyn Storage,
+    submission_id: i32,
+    page: i32,
+) -> Result<Option<(Option<String>, Bytes)>> {
+    let (key, legacy_data) = if page == 1 {
+        let Some((key, legacy_data)) = submission::Entity::find_by_id(submission_id)
+            .select_only()
+            .column(submission::Column::DataKey)
+            .column(submission::Column::Data)
+            .into_tuple::<(Option<String>, Option<Vec<u8>>)>()
+            .one(db)
+            .await
+            .context("failed to query database")?
+        else {
+            return Ok(None);
+        };
+        (key, legacy_data)
+    } else {
+        let Some(key) = submission_page::Entity::find()
+            .filter(submission_page::Column::SubmissionId.eq(submission_id))
+            .filter(submission_page::Column::Page.eq(page))
+            .select_only()
+            .column(submission_page::Column::DataKey)
+            .into_tuple::<String>()
+            .one(db)
+            .await
+            .context("failed to query database")?
+        else {
+            return Ok(None);
+        };
+        (Some(key), None)
+    };
+
+    let data = match &key {
+        Some(key) => storage
+            .get(key)
+            .await
+            .context("failed to get image from storage")?,
+        None => legacy_data.map(Bytes::from),
+    };
+    Ok(data.map(|data| (key, data)))
+}
+
+/// Decodes the image and encodes it in the sizes and formats, fitting it once per size.
+fn render(
+    data: &[u8],
+    targets: Vec<(&'static ImageSize, DerivativeFormat)>,
+) -> Result<Vec<(&'static ImageSize, DerivativeFormat, Vec<u8>)>> {
+    let image_format = ImageFormat::sniff(data).context("unknown image format")?;
+    let image = media::decode_image(data, image_format)?;
+
+    let mut fitted = None;
+    let mut rendered = Vec::with_capacity(targets.len());
+    for (size, format) in targets {
+        if !fitted
+            .as_ref()
+            .is_some_and(|(fitted_size, _)| std::ptr::eq(*fitted_size, size))
+        {
+            fitted = Some((size, media::fit_image(&image, size)));
+        }
+        let (_, fitted_image) = fitted.as_ref().unwrap();
+        let data = media::encode_derivative(fitted_image, format)?;
+        rendered.push((size, format, data));
+    }
+    Ok(rendered)
+}
+
+/// Records the derivatives if the page still has the image they were generated from, and
+/// returns the ones not recorded.
+async fn record<'a>(
+    db: &DatabaseConnection,
+    submission_id: i32,
+    page: i32,
+    original_key: Option<String>,
+    derivatives: &'a [Derivative],
+) -> Result<Vec<&'a Derivative>> {
+    let tx = db.begin().await.context("failed to begin transaction")?;
+
+    // Locking the submission waits for updates replacing its images to finish.
+    let Some(key) = submission::Entity::find_by_id(submission_id)
+        .select_only()
+        .column(submission::Column::DataKey)
+        .lock_exclusive()
+        .into_tuple::<Option<String>>()
+        .one(&tx)
+        .await
+        .context("failed to query database")?
+    else {
+        return Ok(derivatives.iter().collect());
+    };
+    let key = if page == 1 {
+        key
+    } else {
+        submission_page::Entity::find()
+            .filter(submission_page::Column::SubmissionId.eq(submission_id))
+            .filter(submission_page::Column::Page.eq(page))
+            .select_only()
+            .column(submission_page::Column::DataKey)
+            .into_tuple::<String>()
+            .one(&tx)
+            .await
+            .context("failed to query database")?
+    };
+    if key != original_key {
+        return Ok(derivatives.iter().collect());
+    }
+
+    let mut unrecorded = Vec::new();
+    for derivative in derivatives {
+        if !insert(&tx, submission_id, page, derivative).await? {
+            unrecorded.push(derivative);
+        }
+    }
+    tx.commit().await.context("failed to commit to database")?;
+
+    Ok(unrecorded)
+}
+
+/// Inserts the derivative unless one in the same size and format is already recorded.
+async fn insert<C>(conn: &C, submission_id: i32, page: i32, derivative: &Derivative) -> Result<bool>
+where
+    C: ConnectionTrait,
+{
+    let rows = submission_derivative::Entity::insert(submission_derivative::ActiveModel {
+        id: ActiveValue::NotSet,
+        submission_id: ActiveValue::Set(submission_id),
+        page: ActiveValue::Set(page),
+        size: ActiveValue::Set(derivative.size.name.clone()),
+        format: ActiveValue::Set(derivative.format),
+        key: ActiveValue::Set(derivative.key.clone()),
+        hash: ActiveValue::Set(derivative.hash.clone()),
+    })
+    .on_conflict(
+        OnConflict::columns([
+            submission_derivative::Column::SubmissionId,
+            submission_derivative::Column::Page,
+            submission_derivative::Column::Size,
+            submission_derivative::Column::Format,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .exec_without_returning(conn)
+    .await
+    .context("failed to insert to database")?;
+    Ok(rows > 0)
+}
+
+async fn discard<'a>(storage: &dyn Storage, derivatives: impl IntoIterator<Item = &'a Derivative>) {
+    for derivative in derivatives {
+        if let Err(err) = storage.delete(&derivative.key).await {
+            tracing::warn!(?err, key = derivative.key, "failed to delete from storage");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duplicate_pages_match_closest_original_page() {
+        let hashes = [0b1111, 0b1111 << 32];
+        let original_hashes = [0b1100, 0b1111 << 32 | 0b11, 0b1110 << 32];
+        assert_eq!(
+            duplicate_pages(&hashes, &original_hashes, 2).collect::<Vec<_>>(),
+            [(1, 1, 2), (2, 3, 1)],
+        );
+        assert_eq!(
+            duplicate_pages(&hashes, &original_hashes, 1).collect::<Vec<_>>(),
+            [(2, 3, 1)],
+        );
+    }
+
+    #[test]
+    fn duplicate_pages_skip_flat_images() {
+        assert_eq!(duplicate_pages(&[0], &[0], 64).count(), 0);
+        assert_eq!(duplicate_pages(&[1], &[0], 64).count(), 0);
+        assert_eq!(duplicate_pages(&[1], &[], 64).count(), 0);
+    }
+}
@@ -2,8 +2,14 @@
 
 pub mod prelude;
 
-pub mod art;
-pub mod art_vote;
+pub mod account;
+pub mod contest;
 pub mod instance;
-pub mod literature;
-pub mod literature_vote;
+pub mod job;
+pub mod jury_score;
+pub mod session;
+pub mod submission;
+pub mod submission_derivative;
+pub mod submission_duplicate;
+pub mod submission_page;
+pub mod vote;
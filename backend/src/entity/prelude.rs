@@ -1,7 +1,15 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.11.2
 
-pub use super::art::Entity as Art;
-pub use super::art_vote::Entity as ArtVote;
+#![allow(unused_imports)]
+
+pub use super::account::Entity as Account;
+pub use super::contest::Entity as Contest;
 pub use super::instance::Entity as Instance;
-pub use super::literature::Entity as Literature;
-pub use super::literature_vote::Entity as LiteratureVote;
+pub use super::job::Entity as Job;
+pub use super::jury_score::Entity as JuryScore;
+pub use super::session::Entity as Session;
+pub use super::submission::Entity as Submission;
+pub use super::submission_derivative::Entity as SubmissionDerivative;
+pub use super::submission_duplicate::Entity as SubmissionDuplicate;
+pub use super::submission_page::Entity as SubmissionPage;
+pub use super::vote::Entity as Vote;
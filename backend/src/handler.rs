@@ -6,7 +6,7 @@ use axum::{
 use sea_orm::DatabaseConnection;
 use tower_http::services::{ServeDir, ServeFile};
 
-use crate::config::CONFIG;
+use crate::{config::CONFIG, storage::Storage};
 
 mod api;
 
@@ -14,6 +14,7 @@ mod api;
 struct AppState {
     http_client: reqwest::Client,
     db: Arc<DatabaseConnection>,
+    storage: Arc<dyn Storage>,
 }
 
 async fn server_header_middleware<B>(req: Request<B>, next: Next<B>) -> Response {
@@ -27,10 +28,11 @@ async fn server_header_middleware<B>(req: Request<B>, next: Next<B>) -> Response
     resp
 }
 
-pub fn create_router(db: DatabaseConnection) -> Router {
+pub fn create_router(db: DatabaseConnection, storage: Arc<dyn Storage>) -> Router {
     let state = AppState {
         http_client: reqwest::Client::new(),
         db: Arc::new(db),
+        storage,
     };
 
     let api = api::create_router();
@@ -38,7 +40,10 @@ pub fn create_router(db: DatabaseConnection) -> Router {
     Router::new()
         .nest("/api", api)
         .with_state(state)
-        .layer(DefaultBodyLimit::max(1024 * 1024 * 50))
+        // Leave some room for the other multipart fields next to the image
+        .layer(DefaultBodyLimit::max(
+            CONFIG.max_upload_size() + 1024 * 1024,
+        ))
         .nest_service(
             "/",
             ServeDir::new(&CONFIG.static_files_directory_path).fallback(ServeFile::new(
@@ -4,18 +4,22 @@ use self::oauth::User;
 
 use super::AppState;
 
+mod admin;
 mod contest;
 mod oauth;
 
 pub(super) fn create_router() -> Router<AppState> {
+    let admin = admin::create_router();
     let contest = contest::create_router();
     let oauth = oauth::create_router();
 
     Router::new()
         .route("/healthz", routing::get(get_healthz))
         .route("/user", routing::get(get_user))
+        .nest("/admin", admin)
         .nest("/contest", contest)
         .nest("/oauth", oauth)
+        .layer(axum::middleware::from_fn(oauth::refresh_cookie_middleware))
 }
 
 async fn get_healthz() -> &'static str {
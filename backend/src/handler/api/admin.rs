@@ -0,0 +1,851 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{self, FromRequestParts},
+    headers::CacheControl,
+    http::{header, request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    routing, Json, Router, TypedHeader,
+};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    config::CONFIG,
+    entity::{
+        account,
+        contest::{self, TieBreak, VotingMethod},
+        session, submission, submission_duplicate, submission_page, vote,
+    },
+    handler::AppState,
+};
+
+use super::{
+    contest::{discard_images, find_image_keys, Contest, IdPath},
+    oauth::User,
+};
+
+fn acct(user: &User) -> String {
+    format!("{}@{}", user.account_id, user.instance)
+}
+
+fn is_admin(user: &User) -> bool {
+    CONFIG.admin_accounts.contains(&acct(user))
+}
+
+fn is_moderator(user: &User) -> bool {
+    let acct = acct(user);
+    CONFIG.admin_accounts.contains(&acct) || CONFIG.moderator_accounts.contains(&acct)
+}
+
+fn is_judge(user: &User) -> bool {
+    CONFIG.judge_accounts.contains(&acct(user))
+}
+
+pub struct Admin(pub User);
+
+#[async_trait]
+impl FromRequestParts<AppState> for Admin {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user = User::from_request_parts(parts, state).await?;
+        if !is_admin(&user) {
+            return Err((StatusCode::FORBIDDEN, "user not admin"));
+        }
+        Ok(Self(user))
+    }
+}
+
+pub struct Moderator(pub User);
+
+#[async_trait]
+impl FromRequestParts<AppState> for Moderator {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user = User::from_request_parts(parts, state).await?;
+        if !is_moderator(&user) {
+            return Err((StatusCode::FORBIDDEN, "user not moderator"));
+        }
+        Ok(Self(user))
+    }
+}
+
+pub struct Judge(pub User);
+
+#[async_trait]
+impl FromRequestParts<AppState> for Judge {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user = User::from_request_parts(parts, state).await?;
+        if !is_judge(&user) {
+            return Err((StatusCode::FORBIDDEN, "user not judge"));
+        }
+        Ok(Self(user))
+    }
+}
+
+pub(super) fn create_router() -> Router<AppState> {
+    let contest = Router::new()
+        .route("/submission", routing::get(get_submissions))
+        .route("/submission/:id", routing::delete(delete_submission))
+        .route(
+            "/submission/:id/original",
+            routing::get(get_submission_original),
+        )
+        .route(
+            "/submission/:id/hidden",
+            routing::put(put_submission_hidden),
+        )
+        .route(
+            "/submission/:id/tie-break",
+            routing::put(put_submission_tie_break),
+        )
+        .route("/submission/:id/vote", routing::get(get_votes))
+        .route(
+            "/submission/:id/vote/:vote_id",
+            routing::delete(delete_vote),
+        )
+        .route("/participant", routing::get(get_participants))
+        .route("/duplicate", routing::get(get_duplicates));
+
+    Router::new()
+        .route("/role", routing::get(get_role))
+        .route("/contest", routing::post(post_contest))
+        .route("/contest/:slug", routing::put(put_contest))
+        .route("/user/:acct/session", routing::delete(delete_sessions))
+        .nest("/contest/:slug", contest)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetRoleResp {
+    admin: bool,
+    moderator: bool,
+    judge: bool,
+}
+
+async fn get_role(user: User) -> Json<GetRoleResp> {
+    Json(GetRoleResp {
+        admin: is_admin(&user),
+        moderator: is_moderator(&user),
+        judge: is_judge(&user),
+    })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PutContestReq {
+    name: String,
+    #[serde(with = "time::serde::rfc3339")]
+    submission_open_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    submission_close_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    voting_open_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    voting_close_at: OffsetDateTime,
+    categories: Vec<String>,
+    #[serde(default)]
+    voting_method: VotingMethod,
+    #[serde(default)]
+    tie_break: TieBreak,
+    #[serde(default)]
+    blind: bool,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    judging_open_at: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    judging_close_at: Option<OffsetDateTime>,
+    #[serde(default)]
+    jury_weight: f64,
+}
+
+impl PutContestReq {
+    fn validate(&self) -> Result<(), (StatusCode, &'static str)> {
+        if self.name.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "empty contest name"));
+        }
+        if self.submission_open_at > self.submission_close_at
+            || self.voting_open_at > self.voting_close_at
+        {
+            return Err((StatusCode::BAD_REQUEST, "invalid contest schedule"));
+        }
+        if let (Some(judging_open_at), Some(judging_close_at)) =
+            (self.judging_open_at, self.judging_close_at)
+        {
+            if judging_open_at > judging_close_at {
+                return Err((StatusCode::BAD_REQUEST, "invalid contest schedule"));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.jury_weight) {
+            return Err((StatusCode::BAD_REQUEST, "invalid jury weight"));
+        }
+        if self.categories.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "no category"));
+        }
+        if self
+            .categories
+            .iter()
+            .any(|category| CONFIG.category(category).is_none())
+        {
+            return Err((StatusCode::BAD_REQUEST, "unknown category"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostContestReq {
+    slug: String,
+    #[serde(flatten)]
+    contest: PutContestReq,
+}
+
+async fn post_contest(
+    Admin(user): Admin,
+    extract::State(state): extract::State<AppState>,
+    Json(req): Json<PostContestReq>,
+) -> Result<Json<contest::Model>, (StatusCode, &'static str)> {
+    if req.slug.is_empty()
+        || !req
+            .slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        return Err((StatusCode::BAD_REQUEST, "invalid contest slug"));
+    }
+    req.contest.validate()?;
+
+    let existing_contest = contest::Entity::find()
+        .filter(contest::Column::Slug.eq(&req.slug))
+        .count(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?;
+    if existing_contest > 0 {
+        return Err((StatusCode::CONFLICT, "contest already exists"));
+    }
+
+    let contest_activemodel = contest::ActiveModel {
+        id: ActiveValue::NotSet,
+        slug: ActiveValue::Set(req.slug),
+        name: ActiveValue::Set(req.contest.name),
+        submission_open_at: ActiveValue::Set(req.contest.submission_open_at),
+        submission_close_at: ActiveValue::Set(req.contest.submission_close_at),
+        voting_open_at: ActiveValue::Set(req.contest.voting_open_at),
+        voting_close_at: ActiveValue::Set(req.contest.voting_close_at),
+        categories: ActiveValue::Set(req.contest.categories),
+        voting_method: ActiveValue::Set(req.contest.voting_method),
+        tie_break: ActiveValue::Set(req.contest.tie_break),
+        blind: ActiveValue::Set(req.contest.blind),
+        judging_open_at: ActiveValue::Set(req.contest.judging_open_at),
+        judging_close_at: ActiveValue::Set(req.contest.judging_close_at),
+        jury_weight: ActiveValue::Set(req.contest.jury_weight),
+    };
+
+    let contest = contest_activemodel
+        .insert(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to insert to database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to insert to database",
+            )
+        })?;
+
+    tracing::info!(
+        actor = %format!("{}@{}", user.handle, user.instance),
+        slug = %contest.slug,
+        "contest created"
+    );
+
+    Ok(Json(contest))
+}
+
+async fn put_contest(
+    Admin(user): Admin,
+    Contest(contest): Contest,
+    extract::State(state): extract::State<AppState>,
+    Json(req): Json<PutContestReq>,
+) -> Result<Json<contest::Model>, (StatusCode, &'static str)> {
+    req.validate()?;
+
+    let mut contest_activemodel: contest::ActiveModel = contest.into();
+    contest_activemodel.name = ActiveValue::Set(req.name);
+    contest_activemodel.submission_open_at = ActiveValue::Set(req.submission_open_at);
+    contest_activemodel.submission_close_at = ActiveValue::Set(req.submission_close_at);
+    contest_activemodel.voting_open_at = ActiveValue::Set(req.voting_open_at);
+    contest_activemodel.voting_close_at = ActiveValue::Set(req.voting_close_at);
+    contest_activemodel.categories = ActiveValue::Set(req.categories);
+    contest_activemodel.voting_method = ActiveValue::Set(req.voting_method);
+    contest_activemodel.tie_break = ActiveValue::Set(req.tie_break);
+    contest_activemodel.blind = ActiveValue::Set(req.blind);
+    contest_activemodel.judging_open_at = ActiveValue::Set(req.judging_open_at);
+    contest_activemodel.judging_close_at = ActiveValue::Set(req.judging_close_at);
+    contest_activemodel.jury_weight = ActiveValue::Set(req.jury_weight);
+
+    let contest = contest_activemodel
+        .update(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to update database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to update database",
+            )
+        })?;
+
+    tracing::info!(
+        actor = %format!("{}@{}", user.handle, user.instance),
+        slug = %contest.slug,
+        "contest updated"
+    );
+
+    Ok(Json(contest))
+}
+
+#[derive(Deserialize)]
+struct AcctPath {
+    acct: String,
+}
+
+/// Revokes every session of the `handle@instance` account, such as when it was stolen. The
+/// account can log in again. The handle is the one the account last logged in with, so if
+/// another account held it before a rename and has not logged in since, its sessions are revoked
+/// too.
+async fn delete_sessions(
+    Admin(user): Admin,
+    extract::Path(AcctPath { acct }): extract::Path<AcctPath>,
+    extract::State(state): extract::State<AppState>,
+) -> Result<(), (StatusCode, &'static str)> {
+    let (handle, instance) = acct
+        .split_once('@')
+        .ok_or((StatusCode::BAD_REQUEST, "invalid account"))?;
+
+    let account_ids = account::Entity::find()
+        .select_only()
+        .column(account::Column::AccountId)
+        .filter(account::Column::Handle.eq(handle))
+        .filter(account::Column::Instance.eq(instance))
+        .into_tuple::<String>()
+        .all(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?;
+    if account_ids.is_empty() {
+        return Err((StatusCode::NOT_FOUND, "account not found"));
+    }
+
+    let res = session::Entity::delete_many()
+        .filter(session::Column::AccountId.is_in(account_ids.iter()))
+        .filter(session::Column::Instance.eq(instance))
+        .exec(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to delete from database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to delete from database",
+            )
+        })?;
+
+    tracing::info!(
+        actor = %format!("{}@{}", user.handle, user.instance),
+        acct,
+        ?account_ids,
+        sessions = res.rows_affected,
+        "sessions revoked"
+    );
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PutHiddenReq {
+    hidden: bool,
+}
+
+#[derive(Deserialize)]
+struct VotePath {
+    id: i32,
+    vote_id: i32,
+}
+
+async fn get_submissions(
+    _: Moderator,
+    Contest(contest): Contest,
+    extract::State(state): extract::State<AppState>,
+) -> Result<Json<Vec<submission::AdminMetadata>>, (StatusCode, &'static str)> {
+    let submissions = submission::Entity::find()
+        .filter(submission::Column::ContestId.eq(contest.id))
+        .order_by_desc(submission::Column::Id)
+        .into_partial_model::<submission::AdminMetadata>()
+        .all(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?;
+
+    Ok(Json(submissions))
+}
+
+/// Image of a submission resembling an image of an earlier submission.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Duplicate {
+    submission: submission::HashedImages,
+    page: i32,
+    original: submission::HashedImages,
+    original_page: i32,
+    /// Number of differing bits of the perceptual hashes, 0 for practically identical images.
+    distance: u32,
+}
+
+/// Submissions of the contest with images resembling those submitted earlier, to this or any
+/// other contest, for organisers to review. The closest matches come first. Images are compared
+/// when they are hashed after upload, so the latest ones may not be listed yet.
+async fn get_duplicates(
+    _: Moderator,
+    Contest(contest): Contest,
+    extract::State(state): extract::State<AppState>,
+) -> Result<Json<Vec<Duplicate>>, (StatusCode, &'static str)> {
+    let rows = submission_duplicate::Entity::find()
+        .inner_join(submission::Entity)
+        .filter(submission::Column::ContestId.eq(contest.id))
+        .filter(submission_duplicate::Column::Distance.lte(CONFIG.duplicate_max_distance))
+        .order_by_asc(submission_duplicate::Column::Distance)
+        .order_by_asc(submission_duplicate::Column::SubmissionId)
+        .order_by_asc(submission_duplicate::Column::Page)
+        .order_by_asc(submission_duplicate::Column::OriginalId)
+        .all(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?;
+
+    let ids = rows
+        .iter()
+        .flat_map(|row| [row.submission_id, row.original_id])
+        .collect::<BTreeSet<_>>();
+    let submissions = submission::Entity::find()
+        .filter(submission::Column::Id.is_in(ids))
+        .into_partial_model::<submission::HashedImages>()
+        .all(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?
+        .into_iter()
+        .map(|submission| (submission.id, submission))
+        .collect::<BTreeMap<_, _>>();
+
+    Ok(Json(
+        rows.into_iter()
+            .filter_map(|row| {
+                Some(Duplicate {
+                    submission: submissions.get(&row.submission_id)?.clone(),
+                    page: row.page,
+                    original: submissions.get(&row.original_id)?.clone(),
+                    original_page: row.original_page,
+                    distance: row.distance as u32,
+                })
+            })
+            .collect(),
+    ))
+}
+
+async fn put_submission_hidden(
+    Moderator(user): Moderator,
+    Contest(contest): Contest,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
+    extract::State(state): extract::State<AppState>,
+    Json(req): Json<PutHiddenReq>,
+) -> Result<(), (StatusCode, &'static str)> {
+    let res = submission::Entity::update_many()
+        .col_expr(submission::Column::IsHidden, Expr::value(req.hidden))
+        .filter(submission::Column::Id.eq(id))
+        .filter(submission::Column::ContestId.eq(contest.id))
+        .exec(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to update database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to update database",
+            )
+        })?;
+    if res.rows_affected == 0 {
+        return Err((StatusCode::NOT_FOUND, "submission not found"));
+    }
+
+    tracing::info!(
+        actor = %format!("{}@{}", user.handle, user.instance),
+        id, hidden = req.hidden,
+        "submission hidden changed"
+    );
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PutTieBreakReq {
+    /// Lower wins among tied submissions; `None` leaves the submission tied.
+    priority: Option<i32>,
+}
+
+async fn put_submission_tie_break(
+    Admin(user): Admin,
+    Contest(contest): Contest,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
+    extract::State(state): extract::State<AppState>,
+    Json(req): Json<PutTieBreakReq>,
+) -> Result<(), (StatusCode, &'static str)> {
+    let res = submission::Entity::update_many()
+        .col_expr(
+            submission::Column::TieBreakPriority,
+            Expr::value(req.priority),
+        )
+        .filter(submission::Column::Id.eq(id))
+        .filter(submission::Column::ContestId.eq(contest.id))
+        .exec(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to update database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to update database",
+            )
+        })?;
+    if res.rows_affected == 0 {
+        return Err((StatusCode::NOT_FOUND, "submission not found"));
+    }
+
+    tracing::info!(
+        actor = %format!("{}@{}", user.handle, user.instance),
+        id, priority = ?req.priority,
+        "submission tie-break priority changed"
+    );
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct OriginalQuery {
+    page: Option<i32>,
+}
+
+/// Image of a page as uploaded, with its metadata intact. Only kept if private originals are
+/// enabled.
+async fn get_submission_original(
+    _: Admin,
+    Contest(contest): Contest,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
+    extract::Query(OriginalQuery { page }): extract::Query<OriginalQuery>,
+    extract::State(state): extract::State<AppState>,
+) -> Result<Response, (StatusCode, &'static str)> {
+    let (mut private_data_key, mut mime_type) = submission::Entity::find_by_id(id)
+        .filter(submission::Column::ContestId.eq(contest.id))
+        .select_only()
+        .column(submission::Column::PrivateDataKey)
+        .column(submission::Column::MimeType)
+        .into_tuple::<(Option<String>, Option<String>)>()
+        .one(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "submission not found"))?;
+    if let Some(page) = page.filter(|&page| page != 1) {
+        let submission_page = submission_page::Entity::find()
+            .filter(submission_page::Column::SubmissionId.eq(id))
+            .filter(submission_page::Column::Page.eq(page))
+            .one(&*state.db)
+            .await
+            .map_err(|err| {
+                tracing::error!(?err, "failed to query database");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed to query database",
+                )
+            })?
+            .ok_or((StatusCode::NOT_FOUND, "page not found"))?;
+        private_data_key = submission_page.private_data_key;
+        mime_type = Some(submission_page.mime_type);
+    }
+    let private_data_key =
+        private_data_key.ok_or((StatusCode::NOT_FOUND, "private original not found"))?;
+
+    let data = state
+        .storage
+        .get(&private_data_key)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to read from storage");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to read from storage",
+            )
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "private original not found"))?;
+
+    Ok((
+        [(
+            header::CONTENT_TYPE,
+            mime_type.unwrap_or_else(|| mime::APPLICATION_OCTET_STREAM.to_string()),
+        )],
+        TypedHeader(CacheControl::new().with_private().with_no_store()),
+        data,
+    )
+        .into_response())
+}
+
+async fn delete_submission(
+    Moderator(user): Moderator,
+    Contest(contest): Contest,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
+    extract::State(state): extract::State<AppState>,
+) -> Result<(), (StatusCode, &'static str)> {
+    let tx = state.db.begin().await.map_err(|err| {
+        tracing::error!(?err, "failed to begin transaction");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to begin transaction",
+        )
+    })?;
+
+    // Locking the submission waits for updates replacing its images to finish, so the keys
+    // read are the ones deleted with it.
+    let (data_key, thumbnail_key, private_data_key) = submission::Entity::find_by_id(id)
+        .filter(submission::Column::ContestId.eq(contest.id))
+        .select_only()
+        .column(submission::Column::DataKey)
+        .column(submission::Column::ThumbnailKey)
+        .column(submission::Column::PrivateDataKey)
+        .lock_exclusive()
+        .into_tuple::<(Option<String>, Option<String>, Option<String>)>()
+        .one(&tx)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "submission not found"))?;
+    let image_keys = find_image_keys(&tx, id).await?;
+
+    submission::Entity::delete_by_id(id)
+        .exec(&tx)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to delete from database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to delete from database",
+            )
+        })?;
+
+    tx.commit().await.map_err(|err| {
+        tracing::error!(?err, "failed to commit to database");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to commit to database",
+        )
+    })?;
+
+    discard_images(
+        &*state.storage,
+        data_key
+            .into_iter()
+            .chain(thumbnail_key)
+            .chain(private_data_key)
+            .chain(image_keys),
+    )
+    .await;
+
+    tracing::info!(
+        actor = %format!("{}@{}", user.handle, user.instance),
+        id,
+        "submission deleted"
+    );
+
+    Ok(())
+}
+
+async fn get_votes(
+    _: Moderator,
+    Contest(contest): Contest,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
+    extract::State(state): extract::State<AppState>,
+) -> Result<Json<Vec<vote::Model>>, (StatusCode, &'static str)> {
+    let votes = vote::Entity::find()
+        .filter(vote::Column::SubmissionId.eq(id))
+        .filter(vote::Column::ContestId.eq(contest.id))
+        .order_by_asc(vote::Column::Id)
+        .all(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?;
+
+    Ok(Json(votes))
+}
+
+async fn delete_vote(
+    Moderator(user): Moderator,
+    Contest(contest): Contest,
+    extract::Path(VotePath { id, vote_id }): extract::Path<VotePath>,
+    extract::State(state): extract::State<AppState>,
+) -> Result<(), (StatusCode, &'static str)> {
+    let res = vote::Entity::delete_many()
+        .filter(vote::Column::Id.eq(vote_id))
+        .filter(vote::Column::SubmissionId.eq(id))
+        .filter(vote::Column::ContestId.eq(contest.id))
+        .exec(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to delete from database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to delete from database",
+            )
+        })?;
+    if res.rows_affected == 0 {
+        return Err((StatusCode::NOT_FOUND, "vote not found"));
+    }
+
+    tracing::info!(
+        actor = %format!("{}@{}", user.handle, user.instance),
+        id, vote_id,
+        "vote deleted"
+    );
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Participant {
+    /// Current handle, as rows follow renames on login.
+    handle: String,
+    instance: String,
+    /// Not known for rows recorded before account IDs that are not claimed yet, which are
+    /// grouped by handle instead.
+    account_id: Option<String>,
+    /// Number of submissions per category.
+    submission_counts: BTreeMap<String, u64>,
+    /// Number of votes per category.
+    vote_counts: BTreeMap<String, u64>,
+}
+
+async fn get_participants(
+    _: Moderator,
+    Contest(contest): Contest,
+    extract::State(state): extract::State<AppState>,
+) -> Result<Json<Vec<Participant>>, (StatusCode, &'static str)> {
+    let map_err = |err| {
+        tracing::error!(?err, "failed to query database");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to query database",
+        )
+    };
+
+    let authors = submission::Entity::find()
+        .select_only()
+        .column(submission::Column::AuthorAccountId)
+        .column(submission::Column::AuthorHandle)
+        .column(submission::Column::AuthorInstance)
+        .column(submission::Column::Category)
+        .filter(submission::Column::ContestId.eq(contest.id))
+        .into_tuple::<(Option<String>, String, String, String)>()
+        .all(&*state.db)
+        .await
+        .map_err(map_err)?;
+    let voters = vote::Entity::find()
+        .select_only()
+        .column(vote::Column::AccountId)
+        .column(vote::Column::Handle)
+        .column(vote::Column::Instance)
+        .column(vote::Column::Category)
+        .filter(vote::Column::ContestId.eq(contest.id))
+        .into_tuple::<(Option<String>, String, String, String)>()
+        .all(&*state.db)
+        .await
+        .map_err(map_err)?;
+
+    let mut participants = BTreeMap::<(String, Result<String, String>), Participant>::new();
+    for (accts, is_vote) in [(authors, false), (voters, true)] {
+        for (account_id, handle, instance, category) in accts {
+            let key = account_id.clone().ok_or_else(|| handle.clone());
+            let participant = participants
+                .entry((instance.clone(), key))
+                .or_insert_with(|| Participant {
+                    handle,
+                    instance,
+                    account_id,
+                    submission_counts: BTreeMap::new(),
+                    vote_counts: BTreeMap::new(),
+                });
+            let counts = if is_vote {
+                &mut participant.vote_counts
+            } else {
+                &mut participant.submission_counts
+            };
+            *counts.entry(category).or_default() += 1;
+        }
+    }
+
+    Ok(Json(participants.into_values().collect()))
+}
@@ -1,18 +1,36 @@
-use axum::{body::Bytes, extract, http::StatusCode, routing, Json, Router};
+use async_trait::async_trait;
+use std::{collections::BTreeMap, ops::Bound};
+
+use axum::{
+    body::{Bytes, StreamBody},
+    extract::{self, FromRequestParts},
+    headers::{CacheControl, ContentRange, ETag, HeaderMapExt, IfNoneMatch, IfRange, Range},
+    http::{header, request::Parts, HeaderMap, StatusCode},
+    response::{IntoResponse, IntoResponseParts, Response},
+    routing, Json, RequestPartsExt, Router, TypedHeader,
+};
 use rand::{rngs::StdRng, seq::SliceRandom};
 use rand_seeder::Seeder;
-use sea_orm::{EntityTrait, QueryOrder};
-use serde::Serialize;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+    Select, Statement,
+};
+use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
 use crate::{
-    config::CONFIG,
-    entity::{art, literature},
+    config::{self, CategoryKind, CONFIG},
+    entity::{self, contest, submission_derivative::DerivativeFormat, submission_page},
     handler::AppState,
+    job::{self, Job},
+    media::ImageFormat,
+    storage::{self, Storage},
+    text,
 };
 
 use super::oauth::User;
 
+mod judging;
 mod result;
 mod submission;
 mod voting;
@@ -27,56 +45,196 @@ struct GetOpenedResp {
     close_at: OffsetDateTime,
 }
 
+#[derive(Deserialize)]
+struct ContestPath {
+    slug: String,
+}
+
+#[derive(Deserialize)]
+struct CategoryPath {
+    category: String,
+}
+
+#[derive(Deserialize)]
+pub(super) struct IdPath {
+    pub id: i32,
+}
+
+/// Page of an image submission, the first one if not in the path.
+#[derive(Deserialize)]
+struct PagePath {
+    id: i32,
+    #[serde(default = "first_page")]
+    page: i32,
+}
+
+fn first_page() -> i32 {
+    1
+}
+
+/// Derivative of an image to serve. The format is negotiated with `Accept` if not given.
+#[derive(Deserialize)]
+struct ImageQuery {
+    size: Option<String>,
+    format: Option<DerivativeFormat>,
+}
+
+pub(super) struct Contest(pub contest::Model);
+
+#[async_trait]
+impl FromRequestParts<AppState> for Contest {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let extract::Path(ContestPath { slug }) = parts
+            .extract::<extract::Path<ContestPath>>()
+            .await
+            .map_err(|_| (StatusCode::BAD_REQUEST, "invalid contest path"))?;
+
+        let contest = contest::Entity::find()
+            .filter(contest::Column::Slug.eq(slug))
+            .one(&*state.db)
+            .await
+            .map_err(|err| {
+                tracing::error!(?err, "failed to query database");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed to query database",
+                )
+            })?
+            .ok_or((StatusCode::NOT_FOUND, "contest not found"))?;
+
+        Ok(Self(contest))
+    }
+}
+
+/// Contest and one of its enabled categories, from the `:slug` and `:category` path params.
+pub(super) struct ContestCategory(pub contest::Model, pub &'static config::Category);
+
+#[async_trait]
+impl FromRequestParts<AppState> for ContestCategory {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let extract::Path(CategoryPath { category }) = parts
+            .extract::<extract::Path<CategoryPath>>()
+            .await
+            .map_err(|_| (StatusCode::BAD_REQUEST, "invalid category path"))?;
+        let category = CONFIG
+            .category(&category)
+            .ok_or((StatusCode::NOT_FOUND, "category not found"))?;
+
+        let Contest(contest) = Contest::from_request_parts(parts, state).await?;
+        if !contest.categories.contains(&category.slug) {
+            return Err((StatusCode::BAD_REQUEST, "category not enabled"));
+        }
+
+        Ok(Self(contest, category))
+    }
+}
+
+/// Whether authors are hidden from voters, which lasts until voting closes in blind contests.
+pub(super) fn is_author_hidden(contest: &contest::Model) -> bool {
+    contest.blind && OffsetDateTime::now_utc() <= contest.voting_close_at
+}
+
 pub(super) fn create_router() -> Router<AppState> {
+    let judging = judging::create_router();
     let result = result::create_router();
     let submission = submission::create_router();
     let voting = voting::create_router();
 
-    Router::new()
+    let contest = Router::new()
         .route("/name", routing::get(get_name))
         .route("/enabled", routing::get(get_enabled))
+        .route("/rules", routing::get(get_rules))
+        .route("/:category/metadata", routing::get(get_metadata_list))
+        .route("/:category/metadata/:id", routing::get(get_metadata))
+        .route("/:category/:id", routing::get(get_content))
+        .route("/:category/:id/:page", routing::get(get_content))
+        .route("/:category/thumbnail/:id", routing::get(get_thumbnail))
         .route(
-            "/literature/metadata",
-            routing::get(get_literature_metadata_list),
+            "/:category/thumbnail/:id/:page",
+            routing::get(get_thumbnail),
         )
-        .route("/literature/:id", routing::get(get_literature))
-        .route("/art/:id", routing::get(get_art))
-        .route("/art/thumbnail/:id", routing::get(get_art_thumbnail))
-        .route("/art/metadata", routing::get(get_art_metadata_list))
-        .route("/art/metadata/:id", routing::get(get_art_metadata))
+        .nest("/judging", judging)
         .nest("/result", result)
         .nest("/submission", submission)
-        .nest("/voting", voting)
+        .nest("/voting", voting);
+
+    Router::new()
+        .route("/", routing::get(get_contests))
+        .nest("/:slug", contest)
 }
 
-async fn get_name() -> String {
-    CONFIG.contest_name.clone()
+async fn get_contests(
+    extract::State(state): extract::State<AppState>,
+) -> Result<Json<Vec<contest::Model>>, (StatusCode, &'static str)> {
+    let contests = contest::Entity::find()
+        .order_by_desc(contest::Column::SubmissionOpenAt)
+        .order_by_desc(contest::Column::Id)
+        .all(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?;
+
+    Ok(Json(contests))
 }
 
-#[derive(Serialize)]
-struct GetEnabledResp {
-    literature: bool,
-    art: bool,
+async fn get_name(Contest(contest): Contest) -> String {
+    contest.name
 }
 
-async fn get_enabled() -> Json<GetEnabledResp> {
-    Json(GetEnabledResp {
-        literature: CONFIG.literature_enabled,
-        art: CONFIG.art_enabled,
-    })
+async fn get_enabled(Contest(contest): Contest) -> Json<BTreeMap<&'static str, bool>> {
+    Json(
+        CONFIG
+            .categories
+            .iter()
+            .map(|category| {
+                (
+                    category.slug.as_str(),
+                    contest.categories.contains(&category.slug),
+                )
+            })
+            .collect(),
+    )
 }
 
-async fn get_literature_metadata_list(
+async fn get_rules(
+    Contest(contest): Contest,
+) -> Json<BTreeMap<&'static str, &'static config::Category>> {
+    Json(
+        CONFIG
+            .categories
+            .iter()
+            .filter(|category| contest.categories.contains(&category.slug))
+            .map(|category| (category.slug.as_str(), category))
+            .collect(),
+    )
+}
+
+async fn get_metadata_list(
     user: Option<User>,
+    ContestCategory(contest, category): ContestCategory,
     extract::State(state): extract::State<AppState>,
-) -> Result<Json<Vec<literature::Metadata>>, (StatusCode, &'static str)> {
-    if !CONFIG.literature_enabled {
-        return Err((StatusCode::BAD_REQUEST, "literature not enabled"));
-    }
-
-    let mut literatures = literature::Entity::find()
-        .order_by_desc(literature::Column::Id)
-        .into_partial_model::<literature::Metadata>()
+) -> Result<Json<Vec<entity::submission::Metadata>>, (StatusCode, &'static str)> {
+    let mut submissions = entity::submission::Entity::find()
+        .filter(entity::submission::Column::ContestId.eq(contest.id))
+        .filter(entity::submission::Column::Category.eq(&category.slug))
+        .filter(entity::submission::Column::IsHidden.eq(false))
+        .order_by_desc(entity::submission::Column::Id)
+        .into_partial_model::<entity::submission::Metadata>()
         .all(&*state.db)
         .await
         .map_err(|err| {
@@ -89,22 +247,29 @@ async fn get_literature_metadata_list(
 
     if let Some(user) = user {
         let mut rng: StdRng =
-            Seeder::from(&format!("{}@{}", user.handle, user.instance)).make_rng();
-        literatures.shuffle(&mut rng);
+            Seeder::from(&format!("{}@{}", user.account_id, user.instance)).make_rng();
+        submissions.shuffle(&mut rng);
+    }
+
+    if is_author_hidden(&contest) {
+        submissions
+            .iter_mut()
+            .for_each(entity::submission::Metadata::redact_author);
     }
 
-    Ok(Json(literatures))
+    Ok(Json(submissions))
 }
 
-async fn get_literature(
-    extract::Path(id): extract::Path<i32>,
+async fn get_metadata(
+    ContestCategory(contest, category): ContestCategory,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
     extract::State(state): extract::State<AppState>,
-) -> Result<Json<literature::Model>, (StatusCode, &'static str)> {
-    if !CONFIG.literature_enabled {
-        return Err((StatusCode::BAD_REQUEST, "literature not enabled"));
-    }
-
-    let literature = literature::Entity::find_by_id(id)
+) -> Result<Json<entity::submission::Metadata>, (StatusCode, &'static str)> {
+    let mut submission = entity::submission::Entity::find_by_id(id)
+        .filter(entity::submission::Column::ContestId.eq(contest.id))
+        .filter(entity::submission::Column::Category.eq(&category.slug))
+        .filter(entity::submission::Column::IsHidden.eq(false))
+        .into_partial_model::<entity::submission::Metadata>()
         .one(&*state.db)
         .await
         .map_err(|err| {
@@ -114,20 +279,163 @@ async fn get_literature(
                 "failed to query database",
             )
         })?
-        .ok_or((StatusCode::NOT_FOUND, "literature not found"))?;
+        .ok_or((StatusCode::NOT_FOUND, "submission not found"))?;
+
+    if is_author_hidden(&contest) {
+        submission.redact_author();
+    }
 
-    Ok(Json(literature))
+    Ok(Json(submission))
 }
 
-async fn get_art(
-    extract::Path(id): extract::Path<i32>,
+/// Text submissions are returned as JSON with their metadata and rendered HTML, images as the
+/// original image or the derivative in the requested size of the page.
+async fn get_content(
+    headers: HeaderMap,
+    ContestCategory(contest, category): ContestCategory,
+    extract::Path(PagePath { id, page }): extract::Path<PagePath>,
+    extract::Query(image_query): extract::Query<ImageQuery>,
     extract::State(state): extract::State<AppState>,
-) -> Result<Bytes, (StatusCode, &'static str)> {
-    if !CONFIG.art_enabled {
-        return Err((StatusCode::BAD_REQUEST, "art not enabled"));
+) -> Result<Response, (StatusCode, &'static str)> {
+    let query = entity::submission::Entity::find_by_id(id)
+        .filter(entity::submission::Column::ContestId.eq(contest.id))
+        .filter(entity::submission::Column::Category.eq(&category.slug))
+        .filter(entity::submission::Column::IsHidden.eq(false));
+
+    match category.kind {
+        CategoryKind::Text if page != 1 => Err((StatusCode::NOT_FOUND, "page not found")),
+        CategoryKind::Text => {
+            let mut submission = query
+                .into_partial_model::<entity::submission::TextContent>()
+                .one(&*state.db)
+                .await
+                .map_err(|err| {
+                    tracing::error!(?err, "failed to query database");
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "failed to query database",
+                    )
+                })?
+                .ok_or((StatusCode::NOT_FOUND, "submission not found"))?;
+
+            if is_author_hidden(&contest) {
+                submission.redact_author();
+            }
+
+            let html = text::render_html(
+                submission.text.as_deref().unwrap_or_default(),
+                submission.text_format.unwrap_or_default(),
+            );
+            Ok(Json(TextContentResp { submission, html }).into_response())
+        }
+        CategoryKind::Image if image_query.size.is_some() => {
+            get_derivative(&headers, &state, query, page, image_query).await
+        }
+        CategoryKind::Image => original_response(&headers, &state, query, page).await,
+    }
+}
+
+/// Serves the original image of a page of the submission selected by the query.
+async fn original_response(
+    headers: &HeaderMap,
+    state: &AppState,
+    query: Select<entity::submission::Entity>,
+    page: i32,
+) -> Result<Response, (StatusCode, &'static str)> {
+    let PageImage {
+        data_key,
+        data,
+        data_hash,
+        mime_type,
+    } = find_page_image(state, query, page).await?;
+
+    if let Some(data_hash) = &data_hash {
+        if is_not_modified(headers, data_hash) {
+            return Ok(not_modified_response(data_hash));
+        }
+    }
+
+    // Images in the storage backend have their hash and type recorded when they are put.
+    if let (Some(data_key), Some(data_hash)) = (&data_key, &data_hash) {
+        let content_type = mime_type.unwrap_or_else(|| mime::APPLICATION_OCTET_STREAM.to_string());
+        return stored_image_response(headers, &*state.storage, data_key, &content_type, data_hash)
+            .await;
+    }
+
+    let data = load_image(state, data_key, data)
+        .await?
+        .ok_or((StatusCode::NOT_FOUND, "submission not found"))?;
+    let data_hash = data_hash.unwrap_or_else(|| storage::content_hash(&data));
+    let content_type = mime_type
+        .or_else(|| ImageFormat::sniff(&data).map(|format| format.mime().to_string()))
+        .unwrap_or_else(|| mime::APPLICATION_OCTET_STREAM.to_string());
+
+    Ok(image_response(
+        headers,
+        data,
+        &content_type,
+        &data_hash,
+        true,
+    ))
+}
+
+#[derive(Serialize)]
+struct TextContentResp {
+    #[serde(flatten)]
+    submission: entity::submission::TextContent,
+    /// Text rendered to sanitized HTML.
+    html: String,
+}
+
+/// Original image of a page, which is kept in the submission itself for the first page.
+struct PageImage {
+    data_key: Option<String>,
+    /// Image submitted before the storage backend existed.
+    data: Option<Vec<u8>>,
+    data_hash: Option<String>,
+    mime_type: Option<String>,
+}
+
+async fn find_page_image(
+    state: &AppState,
+    query: Select<entity::submission::Entity>,
+    page: i32,
+) -> Result<PageImage, (StatusCode, &'static str)> {
+    if page == 1 {
+        let (data_key, data, data_hash, mime_type) = query
+            .select_only()
+            .column(entity::submission::Column::DataKey)
+            .column(entity::submission::Column::Data)
+            .column(entity::submission::Column::DataHash)
+            .column(entity::submission::Column::MimeType)
+            .into_tuple::<(
+                Option<String>,
+                Option<Vec<u8>>,
+                Option<String>,
+                Option<String>,
+            )>()
+            .one(&*state.db)
+            .await
+            .map_err(|err| {
+                tracing::error!(?err, "failed to query database");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed to query database",
+                )
+            })?
+            .ok_or((StatusCode::NOT_FOUND, "submission not found"))?;
+        return Ok(PageImage {
+            data_key,
+            data,
+            data_hash,
+            mime_type,
+        });
     }
 
-    let art = art::Entity::find_by_id(id)
+    let id = find_submission_id(state, query).await?;
+    let page = submission_page::Entity::find()
+        .filter(submission_page::Column::SubmissionId.eq(id))
+        .filter(submission_page::Column::Page.eq(page))
         .one(&*state.db)
         .await
         .map_err(|err| {
@@ -137,20 +445,23 @@ async fn get_art(
                 "failed to query database",
             )
         })?
-        .ok_or((StatusCode::NOT_FOUND, "art not found"))?;
-
-    Ok(Bytes::from(art.data))
+        .ok_or((StatusCode::NOT_FOUND, "page not found"))?;
+    Ok(PageImage {
+        data_key: Some(page.data_key),
+        data: None,
+        data_hash: Some(page.data_hash),
+        mime_type: Some(page.mime_type),
+    })
 }
 
-async fn get_art_thumbnail(
-    extract::Path(id): extract::Path<i32>,
-    extract::State(state): extract::State<AppState>,
-) -> Result<Bytes, (StatusCode, &'static str)> {
-    if !CONFIG.art_enabled {
-        return Err((StatusCode::BAD_REQUEST, "art not enabled"));
-    }
-
-    let art = art::Entity::find_by_id(id)
+async fn find_submission_id(
+    state: &AppState,
+    query: Select<entity::submission::Entity>,
+) -> Result<i32, (StatusCode, &'static str)> {
+    query
+        .select_only()
+        .column(entity::submission::Column::Id)
+        .into_tuple::<i32>()
         .one(&*state.db)
         .await
         .map_err(|err| {
@@ -160,23 +471,275 @@ async fn get_art_thumbnail(
                 "failed to query database",
             )
         })?
-        .ok_or((StatusCode::NOT_FOUND, "art not found"))?;
+        .ok_or((StatusCode::NOT_FOUND, "submission not found"))
+}
 
-    Ok(Bytes::from(art.thumbnail_data))
+/// Reads an image from the storage backend, or from the database if it was submitted before
+/// the storage backend existed and has not been migrated yet.
+async fn load_image(
+    state: &AppState,
+    key: Option<String>,
+    legacy_data: Option<Vec<u8>>,
+) -> Result<Option<Bytes>, (StatusCode, &'static str)> {
+    match key {
+        Some(key) => state.storage.get(&key).await.map_err(|err| {
+            tracing::error!(?err, "failed to read from storage");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to read from storage",
+            )
+        }),
+        None => Ok(legacy_data.map(Bytes::from)),
+    }
 }
 
-async fn get_art_metadata_list(
-    user: Option<User>,
+fn etag(hash: &str) -> ETag {
+    format!("\"{}\"", hash).parse().unwrap()
+}
+
+/// Images are cached but revalidated every time, so updated, hidden or deleted submissions are
+/// never served stale.
+fn cache_control() -> CacheControl {
+    CacheControl::new().with_public().with_no_cache()
+}
+
+/// Whether the client already has the image with the hash, according to `If-None-Match`.
+fn is_not_modified(headers: &HeaderMap, hash: &str) -> bool {
+    headers
+        .typed_get::<IfNoneMatch>()
+        .is_some_and(|if_none_match| !if_none_match.precondition_passes(&etag(hash)))
+}
+
+fn not_modified_response(hash: &str) -> Response {
+    (
+        StatusCode::NOT_MODIFIED,
+        TypedHeader(etag(hash)),
+        TypedHeader(cache_control()),
+    )
+        .into_response()
+}
+
+/// Resolves a single byte range to inclusive offsets within `len` bytes, following RFC 7233.
+fn satisfiable_range((start, end): (Bound<u64>, Bound<u64>), len: u64) -> Option<(u64, u64)> {
+    match (start, end) {
+        (Bound::Included(start), Bound::Included(end)) if start <= end && start < len => {
+            Some((start, end.min(len - 1)))
+        }
+        (Bound::Included(start), Bound::Unbounded) if start < len => Some((start, len - 1)),
+        (Bound::Unbounded, Bound::Included(suffix)) if suffix > 0 && len > 0 => {
+            Some((len.saturating_sub(suffix), len - 1))
+        }
+        _ => None,
+    }
+}
+
+/// Part of an image requested by the headers, for a single-part `Range` only if `ranged`.
+/// Requests for several ranges get the whole image.
+enum ImagePart {
+    NotModified,
+    Whole,
+    Range((Bound<u64>, Bound<u64>)),
+}
+
+fn requested_image_part(headers: &HeaderMap, hash: &str, ranged: bool) -> ImagePart {
+    if is_not_modified(headers, hash) {
+        return ImagePart::NotModified;
+    }
+    if !ranged {
+        return ImagePart::Whole;
+    }
+
+    let range = headers.typed_get::<Range>().filter(|_| {
+        headers.typed_get::<IfRange>().map_or(true, |if_range| {
+            !if_range.is_modified(Some(&etag(hash)), None)
+        })
+    });
+    let mut bounds = range.iter().flat_map(Range::iter);
+    match (bounds.next(), bounds.next()) {
+        (Some(bounds), None) => ImagePart::Range(bounds),
+        _ => ImagePart::Whole,
+    }
+}
+
+fn image_headers(content_type: &str, hash: &str) -> impl IntoResponseParts {
+    (
+        [(header::CONTENT_TYPE, content_type.to_string())],
+        TypedHeader(etag(hash)),
+        TypedHeader(cache_control()),
+    )
+}
+
+fn range_not_satisfiable_response(content_type: &str, hash: &str, len: u64) -> Response {
+    (
+        StatusCode::RANGE_NOT_SATISFIABLE,
+        [(header::ACCEPT_RANGES, "bytes")],
+        image_headers(content_type, hash),
+        TypedHeader(ContentRange::unsatisfied_bytes(len)),
+    )
+        .into_response()
+}
+
+/// Responds with an image in memory, honoring `If-None-Match` and, if `ranged`, a single-part
+/// `Range`.
+fn image_response(
+    headers: &HeaderMap,
+    data: Bytes,
+    content_type: &str,
+    hash: &str,
+    ranged: bool,
+) -> Response {
+    let len = data.len() as u64;
+    let accept_ranges = ranged.then_some([(header::ACCEPT_RANGES, "bytes")]);
+    match requested_image_part(headers, hash, ranged) {
+        ImagePart::NotModified => not_modified_response(hash),
+        ImagePart::Whole => {
+            (accept_ranges, image_headers(content_type, hash), data).into_response()
+        }
+        ImagePart::Range(bounds) => match satisfiable_range(bounds, len) {
+            Some((start, end)) => (
+                StatusCode::PARTIAL_CONTENT,
+                accept_ranges,
+                image_headers(content_type, hash),
+                TypedHeader(ContentRange::bytes(start..=end, len).unwrap()),
+                data.slice(start as usize..=end as usize),
+            )
+                .into_response(),
+            None => range_not_satisfiable_response(content_type, hash, len),
+        },
+    }
+}
+
+/// Responds with an image streamed from the storage backend, honoring `If-None-Match` and a
+/// single-part `Range`, so that large images are never held in memory.
+async fn stored_image_response(
+    headers: &HeaderMap,
+    storage: &dyn Storage,
+    key: &str,
+    content_type: &str,
+    hash: &str,
+) -> Result<Response, (StatusCode, &'static str)> {
+    let map_err = |err| {
+        tracing::error!(?err, "failed to read from storage");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to read from storage",
+        )
+    };
+
+    let (status, content_range, range) = match requested_image_part(headers, hash, true) {
+        ImagePart::NotModified => return Ok(not_modified_response(hash)),
+        ImagePart::Whole => (StatusCode::OK, None, None),
+        ImagePart::Range(bounds) => {
+            let len = storage
+                .size(key)
+                .await
+                .map_err(map_err)?
+                .ok_or((StatusCode::NOT_FOUND, "submission not found"))?;
+            match satisfiable_range(bounds, len) {
+                Some((start, end)) => (
+                    StatusCode::PARTIAL_CONTENT,
+                    Some(TypedHeader(ContentRange::bytes(start..=end, len).unwrap())),
+                    Some(start..=end),
+                ),
+                None => return Ok(range_not_satisfiable_response(content_type, hash, len)),
+            }
+        }
+    };
+
+    let (len, stream) = storage
+        .get_stream(key, range)
+        .await
+        .map_err(map_err)?
+        .ok_or((StatusCode::NOT_FOUND, "submission not found"))?;
+    Ok((
+        status,
+        [(header::ACCEPT_RANGES, "bytes")],
+        image_headers(content_type, hash),
+        [(header::CONTENT_LENGTH, len.to_string())],
+        content_range,
+        StreamBody::new(stream),
+    )
+        .into_response())
+}
+
+/// Removes images which are no longer referenced. Failing only leaves orphaned blobs behind, so
+/// errors are logged and otherwise ignored.
+pub(super) async fn discard_images(storage: &dyn Storage, keys: impl IntoIterator<Item = String>) {
+    for key in keys {
+        if let Err(err) = storage.delete(&key).await {
+            tracing::warn!(?err, key, "failed to delete from storage");
+        }
+    }
+}
+
+/// Serves the derivative of the page in the first configured size unless another one is
+/// requested.
+async fn get_thumbnail(
+    headers: HeaderMap,
+    ContestCategory(contest, category): ContestCategory,
+    extract::Path(PagePath { id, page }): extract::Path<PagePath>,
+    extract::Query(image_query): extract::Query<ImageQuery>,
     extract::State(state): extract::State<AppState>,
-) -> Result<Json<Vec<art::Metadata>>, (StatusCode, &'static str)> {
-    if !CONFIG.art_enabled {
-        return Err((StatusCode::BAD_REQUEST, "art not enabled"));
+) -> Result<Response, (StatusCode, &'static str)> {
+    if category.kind != CategoryKind::Image {
+        return Err((StatusCode::BAD_REQUEST, "category has no image"));
     }
 
-    let mut arts = art::Entity::find()
-        .order_by_desc(art::Column::Id)
-        .into_partial_model::<art::Metadata>()
-        .all(&*state.db)
+    let query = entity::submission::Entity::find_by_id(id)
+        .filter(entity::submission::Column::ContestId.eq(contest.id))
+        .filter(entity::submission::Column::Category.eq(&category.slug))
+        .filter(entity::submission::Column::IsHidden.eq(false));
+
+    get_derivative(&headers, &state, query, page, image_query).await
+}
+
+/// Picks WebP if the client accepts it, PNG otherwise.
+fn negotiate_derivative_format(headers: &HeaderMap) -> DerivativeFormat {
+    let accepts_webp = headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|media_range| {
+            let mut params = media_range.split(';').map(str::trim);
+            params.next() == Some("image/webp")
+                && params.all(|param| param.replace(' ', "") != "q=0")
+        });
+    if accepts_webp {
+        DerivativeFormat::Webp
+    } else {
+        DerivativeFormat::Png
+    }
+}
+
+/// Serves a derivative of a page of the submission selected by the query. If it does not exist
+/// yet, such as for older images or newly configured sizes, a job is enqueued to generate it and
+/// the original image is served meanwhile, so that requests never decode images.
+async fn get_derivative(
+    headers: &HeaderMap,
+    state: &AppState,
+    query: Select<entity::submission::Entity>,
+    page: i32,
+    image_query: ImageQuery,
+) -> Result<Response, (StatusCode, &'static str)> {
+    let size = match &image_query.size {
+        Some(size) => CONFIG
+            .image_size(size)
+            .ok_or((StatusCode::BAD_REQUEST, "unknown image size"))?,
+        None => &CONFIG.image_sizes[0],
+    };
+    let format = image_query
+        .format
+        .unwrap_or_else(|| negotiate_derivative_format(headers));
+    let vary = [(header::VARY, "accept")];
+
+    let id = find_submission_id(state, query).await?;
+    let derivative = entity::submission_derivative::Entity::find()
+        .filter(entity::submission_derivative::Column::SubmissionId.eq(id))
+        .filter(entity::submission_derivative::Column::Page.eq(page))
+        .filter(entity::submission_derivative::Column::Size.eq(&size.name))
+        .filter(entity::submission_derivative::Column::Format.eq(format))
+        .one(&*state.db)
         .await
         .map_err(|err| {
             tracing::error!(?err, "failed to query database");
@@ -186,26 +749,90 @@ async fn get_art_metadata_list(
             )
         })?;
 
-    if let Some(user) = user {
-        let mut rng: StdRng =
-            Seeder::from(&format!("{}@{}", user.handle, user.instance)).make_rng();
-        arts.shuffle(&mut rng);
-    }
+    let data = match &derivative {
+        Some(derivative) => {
+            if is_not_modified(headers, &derivative.hash) {
+                return Ok((vary, not_modified_response(&derivative.hash)).into_response());
+            }
+            state.storage.get(&derivative.key).await.map_err(|err| {
+                tracing::error!(?err, "failed to read from storage");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed to read from storage",
+                )
+            })?
+        }
+        None => None,
+    };
+    let Some((data, derivative)) = data.zip(derivative) else {
+        job::enqueue_unless_queued(&*state.db, &Job::GenerateDerivatives { submission_id: id })
+            .await
+            .map_err(|err| {
+                tracing::error!(?err, "failed to enqueue job");
+                (StatusCode::INTERNAL_SERVER_ERROR, "failed to enqueue job")
+            })?;
+        let query = entity::submission::Entity::find_by_id(id);
+        return Ok((vary, original_response(headers, state, query, page).await?).into_response());
+    };
 
-    Ok(Json(arts))
+    Ok((
+        vary,
+        image_response(
+            headers,
+            data,
+            format.mime().as_ref(),
+            &derivative.hash,
+            false,
+        ),
+    )
+        .into_response())
 }
 
-async fn get_art_metadata(
-    extract::Path(id): extract::Path<i32>,
-    extract::State(state): extract::State<AppState>,
-) -> Result<Json<art::Metadata>, (StatusCode, &'static str)> {
-    if !CONFIG.art_enabled {
-        return Err((StatusCode::BAD_REQUEST, "art not enabled"));
-    }
+/// Makes other transactions of the user adding rows of the kind, such as votes, to the category
+/// wait until the transaction ends, so that limits checked by counting rows hold under
+/// concurrent requests.
+pub(super) async fn lock_user_rows<C>(
+    conn: &C,
+    kind: &str,
+    contest: &contest::Model,
+    category: &config::Category,
+    user: &User,
+) -> Result<(), (StatusCode, &'static str)>
+where
+    C: ConnectionTrait,
+{
+    conn.execute(Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        "SELECT pg_advisory_xact_lock(hashtext($1))",
+        [format!(
+            "{}/{}/{}/{}@{}",
+            kind, contest.id, category.slug, user.account_id, user.instance
+        )
+        .into()],
+    ))
+    .await
+    .map_err(|err| {
+        tracing::error!(?err, "failed to lock database");
+        (StatusCode::INTERNAL_SERVER_ERROR, "failed to lock database")
+    })?;
+    Ok(())
+}
 
-    let art = art::Entity::find_by_id(id)
-        .into_partial_model::<art::Metadata>()
-        .one(&*state.db)
+/// Storage keys of all derivatives of the submission and of the pages after the first, whose
+/// keys are kept in the submission itself.
+pub(super) async fn find_image_keys<C>(
+    conn: &C,
+    submission_id: i32,
+) -> Result<Vec<String>, (StatusCode, &'static str)>
+where
+    C: ConnectionTrait,
+{
+    let mut keys = entity::submission_derivative::Entity::find()
+        .filter(entity::submission_derivative::Column::SubmissionId.eq(submission_id))
+        .select_only()
+        .column(entity::submission_derivative::Column::Key)
+        .into_tuple::<String>()
+        .all(conn)
         .await
         .map_err(|err| {
             tracing::error!(?err, "failed to query database");
@@ -213,8 +840,204 @@ async fn get_art_metadata(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "failed to query database",
             )
-        })?
-        .ok_or((StatusCode::NOT_FOUND, "art not found"))?;
+        })?;
+    let page_keys = submission_page::Entity::find()
+        .filter(submission_page::Column::SubmissionId.eq(submission_id))
+        .select_only()
+        .column(submission_page::Column::DataKey)
+        .column(submission_page::Column::PrivateDataKey)
+        .into_tuple::<(String, Option<String>)>()
+        .all(conn)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?;
+    for (data_key, private_data_key) in page_keys {
+        keys.push(data_key);
+        keys.extend(private_data_key);
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::HttpBody;
+
+    use super::*;
+
+    const HASH: &str = "abc";
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.parse().unwrap(), value.parse().unwrap()))
+            .collect()
+    }
+
+    fn respond(pairs: &[(&'static str, &'static str)], data: &'static [u8]) -> Response {
+        image_response(&headers(pairs), Bytes::from(data), "image/png", HASH, true)
+    }
+
+    async fn body(response: Response) -> Vec<u8> {
+        let mut body = response.into_body();
+        let mut data = Vec::new();
+        while let Some(chunk) = body.data().await {
+            data.extend_from_slice(&chunk.unwrap());
+        }
+        data
+    }
+
+    fn header(response: &Response, name: header::HeaderName) -> Option<&str> {
+        response
+            .headers()
+            .get(name)
+            .map(|value| value.to_str().unwrap())
+    }
+
+    #[test]
+    fn satisfiable_ranges() {
+        use Bound::*;
+
+        assert_eq!(
+            satisfiable_range((Included(0), Included(9)), 100),
+            Some((0, 9))
+        );
+        assert_eq!(
+            satisfiable_range((Included(5), Unbounded), 100),
+            Some((5, 99))
+        );
+        // Ends past the length are clamped.
+        assert_eq!(
+            satisfiable_range((Included(90), Included(200)), 100),
+            Some((90, 99))
+        );
+        // Starts at or past the end are not satisfiable.
+        assert_eq!(satisfiable_range((Included(100), Included(200)), 100), None);
+        assert_eq!(satisfiable_range((Included(100), Unbounded), 100), None);
+        assert_eq!(satisfiable_range((Included(10), Included(5)), 100), None);
+        // Suffixes count from the end, and longer ones are the whole body.
+        assert_eq!(
+            satisfiable_range((Unbounded, Included(10)), 100),
+            Some((90, 99))
+        );
+        assert_eq!(
+            satisfiable_range((Unbounded, Included(200)), 100),
+            Some((0, 99))
+        );
+        assert_eq!(satisfiable_range((Unbounded, Included(0)), 100), None);
+        // Nothing is satisfiable in an empty body.
+        assert_eq!(satisfiable_range((Included(0), Included(0)), 0), None);
+        assert_eq!(satisfiable_range((Included(0), Unbounded), 0), None);
+        assert_eq!(satisfiable_range((Unbounded, Included(1)), 0), None);
+    }
+
+    #[test]
+    fn not_modified_matches() {
+        assert!(!is_not_modified(&headers(&[]), HASH));
+        assert!(is_not_modified(&headers(&[("if-none-match", "*")]), HASH));
+        assert!(is_not_modified(
+            &headers(&[("if-none-match", "\"abc\"")]),
+            HASH
+        ));
+        assert!(is_not_modified(
+            &headers(&[("if-none-match", "W/\"abc\"")]),
+            HASH
+        ));
+        assert!(is_not_modified(
+            &headers(&[("if-none-match", "\"x\", \"abc\"")]),
+            HASH
+        ));
+        assert!(!is_not_modified(
+            &headers(&[("if-none-match", "\"x\"")]),
+            HASH
+        ));
+    }
+
+    #[tokio::test]
+    async fn image_responses() {
+        let data = b"0123456789";
+
+        let response = respond(&[], data);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header(&response, header::ACCEPT_RANGES), Some("bytes"));
+        assert_eq!(header(&response, header::ETAG), Some("\"abc\""));
+        assert_eq!(body(response).await, data);
+
+        let response = respond(&[("if-none-match", "\"abc\"")], data);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(body(response).await.is_empty());
+
+        let response = respond(&[("range", "bytes=2-4")], data);
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(
+            header(&response, header::CONTENT_RANGE),
+            Some("bytes 2-4/10")
+        );
+        assert_eq!(body(response).await, b"234");
+
+        let response = respond(&[("range", "bytes=-3")], data);
+        assert_eq!(
+            header(&response, header::CONTENT_RANGE),
+            Some("bytes 7-9/10")
+        );
+        assert_eq!(body(response).await, b"789");
+
+        let response = respond(&[("range", "bytes=8-100")], data);
+        assert_eq!(
+            header(&response, header::CONTENT_RANGE),
+            Some("bytes 8-9/10")
+        );
+        assert_eq!(body(response).await, b"89");
 
-    Ok(Json(art))
+        let response = respond(&[("range", "bytes=10-")], data);
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(header(&response, header::CONTENT_RANGE), Some("bytes */10"));
+
+        // Several ranges get the whole image.
+        let response = respond(&[("range", "bytes=0-1,4-5")], data);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await, data);
+    }
+
+    #[tokio::test]
+    async fn if_range_mismatch_gets_whole_image() {
+        let data = b"0123456789";
+
+        let response = respond(&[("range", "bytes=0-1"), ("if-range", "\"abc\"")], data);
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(body(response).await, b"01");
+
+        let response = respond(&[("range", "bytes=0-1"), ("if-range", "\"old\"")], data);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await, data);
+    }
+
+    #[tokio::test]
+    async fn empty_images() {
+        let response = respond(&[], b"");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(body(response).await.is_empty());
+
+        let response = respond(&[("range", "bytes=0-")], b"");
+        assert_eq!(response.status(), StatusCode::RANGE_NOT_SATISFIABLE);
+        assert_eq!(header(&response, header::CONTENT_RANGE), Some("bytes */0"));
+    }
+
+    #[tokio::test]
+    async fn unranged_responses_ignore_range() {
+        let response = image_response(
+            &headers(&[("range", "bytes=0-1")]),
+            Bytes::from_static(b"0123456789"),
+            "image/png",
+            HASH,
+            false,
+        );
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header(&response, header::ACCEPT_RANGES), None);
+        assert_eq!(body(response).await, b"0123456789");
+    }
 }
@@ -0,0 +1,204 @@
+use axum::{extract, http::StatusCode, routing, Json, Router};
+use sea_orm::{
+    sea_query::OnConflict, ActiveValue, ColumnTrait, EntityTrait, QueryFilter, QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::{
+    entity::{contest, jury_score, submission},
+    handler::{api::admin::Judge, AppState},
+};
+
+use super::{Contest, ContestCategory, IdPath};
+
+/// Highest score a judge can give to a single submission.
+pub(super) const JURY_MAX_SCORE: i32 = 10;
+
+pub(super) fn create_router() -> Router<AppState> {
+    Router::new()
+        .route("/opened", routing::get(get_opened))
+        .route("/:category", routing::get(get_scores))
+        .route(
+            "/:category/:id",
+            routing::put(put_score).delete(delete_score),
+        )
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GetOpenedResp {
+    opened: bool,
+    #[serde(with = "time::serde::rfc3339::option")]
+    open_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    close_at: Option<OffsetDateTime>,
+}
+
+fn is_judging_opened(contest: &contest::Model) -> bool {
+    let now = OffsetDateTime::now_utc();
+    match (contest.judging_open_at, contest.judging_close_at) {
+        (Some(open_at), Some(close_at)) => now >= open_at && now <= close_at,
+        _ => false,
+    }
+}
+
+async fn get_opened(Contest(contest): Contest) -> Json<GetOpenedResp> {
+    Json(GetOpenedResp {
+        opened: is_judging_opened(&contest),
+        open_at: contest.judging_open_at,
+        close_at: contest.judging_close_at,
+    })
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ScoreEntry {
+    submission_id: i32,
+    score: i32,
+}
+
+/// Scores given by the judge, who only ever sees their own.
+async fn get_scores(
+    Judge(user): Judge,
+    ContestCategory(contest, category): ContestCategory,
+    extract::State(state): extract::State<AppState>,
+) -> Result<Json<Vec<ScoreEntry>>, (StatusCode, &'static str)> {
+    let scores = jury_score::Entity::find()
+        .filter(
+            jury_score::Column::ContestId
+                .eq(contest.id)
+                .and(jury_score::Column::Category.eq(&category.slug))
+                .and(jury_score::Column::AccountId.eq(&user.account_id))
+                .and(jury_score::Column::Instance.eq(&user.instance)),
+        )
+        .order_by_asc(jury_score::Column::SubmissionId)
+        .all(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?;
+
+    Ok(Json(
+        scores
+            .into_iter()
+            .map(|score| ScoreEntry {
+                submission_id: score.submission_id,
+                score: score.score,
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PutScoreReq {
+    score: i32,
+}
+
+async fn put_score(
+    Judge(user): Judge,
+    ContestCategory(contest, category): ContestCategory,
+    extract::State(state): extract::State<AppState>,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
+    Json(req): Json<PutScoreReq>,
+) -> Result<(), (StatusCode, &'static str)> {
+    if !is_judging_opened(&contest) {
+        return Err((StatusCode::BAD_REQUEST, "judging not available"));
+    }
+    if !(0..=JURY_MAX_SCORE).contains(&req.score) {
+        return Err((StatusCode::BAD_REQUEST, "invalid score"));
+    }
+
+    let submission = submission::Entity::find_by_id(id)
+        .filter(submission::Column::ContestId.eq(contest.id))
+        .filter(submission::Column::Category.eq(&category.slug))
+        .filter(submission::Column::IsHidden.eq(false))
+        .one(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "submission not found"))?;
+    if submission.author_account_id.as_ref() == Some(&user.account_id)
+        && submission.author_instance == user.instance
+    {
+        return Err((StatusCode::FORBIDDEN, "cannot judge own submission"));
+    }
+
+    let score_activemodel = jury_score::ActiveModel {
+        id: ActiveValue::NotSet,
+        handle: ActiveValue::Set(user.handle),
+        instance: ActiveValue::Set(user.instance),
+        account_id: ActiveValue::Set(Some(user.account_id)),
+        contest_id: ActiveValue::Set(contest.id),
+        category: ActiveValue::Set(category.slug.clone()),
+        submission_id: ActiveValue::Set(id),
+        score: ActiveValue::Set(req.score),
+    };
+
+    jury_score::Entity::insert(score_activemodel)
+        .on_conflict(
+            OnConflict::columns([
+                jury_score::Column::AccountId,
+                jury_score::Column::Instance,
+                jury_score::Column::SubmissionId,
+            ])
+            .update_column(jury_score::Column::Score)
+            .to_owned(),
+        )
+        .exec(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to insert to database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to insert to database",
+            )
+        })?;
+
+    Ok(())
+}
+
+async fn delete_score(
+    Judge(user): Judge,
+    ContestCategory(contest, category): ContestCategory,
+    extract::State(state): extract::State<AppState>,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
+) -> Result<(), (StatusCode, &'static str)> {
+    if !is_judging_opened(&contest) {
+        return Err((StatusCode::BAD_REQUEST, "judging not available"));
+    }
+
+    let res = jury_score::Entity::delete_many()
+        .filter(
+            jury_score::Column::ContestId
+                .eq(contest.id)
+                .and(jury_score::Column::Category.eq(&category.slug))
+                .and(jury_score::Column::AccountId.eq(&user.account_id))
+                .and(jury_score::Column::Instance.eq(&user.instance))
+                .and(jury_score::Column::SubmissionId.eq(id)),
+        )
+        .exec(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to delete from database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to delete from database",
+            )
+        })?;
+    if res.rows_affected == 0 {
+        return Err((StatusCode::NOT_FOUND, "score not found"));
+    }
+
+    Ok(())
+}
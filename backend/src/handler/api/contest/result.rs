@@ -1,22 +1,24 @@
+use std::collections::{HashMap, HashSet};
+
 use axum::{extract, http::StatusCode, routing, Json, Router};
-use sea_orm::{
-    sea_query::{Alias, Expr},
-    ColumnTrait, EntityTrait, FromQueryResult, PartialModelTrait, QueryOrder, QuerySelect,
-};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
 use serde::Serialize;
 use time::OffsetDateTime;
 
 use crate::{
-    config::CONFIG,
-    entity::{art, art_vote, literature, literature_vote},
+    entity::{
+        contest::{self, TieBreak, VotingMethod},
+        jury_score, submission, vote,
+    },
     handler::AppState,
 };
 
+use super::{judging::JURY_MAX_SCORE, Contest, ContestCategory};
+
 pub(super) fn create_router() -> Router<AppState> {
     Router::new()
         .route("/opened", routing::get(get_opened))
-        .route("/literature", routing::get(get_literature))
-        .route("/art", routing::get(get_art))
+        .route("/:category", routing::get(get_result))
 }
 
 #[derive(Serialize)]
@@ -27,103 +29,480 @@ struct GetOpenedResp {
     open_at: OffsetDateTime,
 }
 
-async fn get_opened() -> Json<GetOpenedResp> {
+/// Results are published once both voting and judging, if any, have closed.
+fn result_open_at(contest: &contest::Model) -> OffsetDateTime {
+    contest
+        .judging_close_at
+        .map_or(contest.voting_close_at, |judging_close_at| {
+            judging_close_at.max(contest.voting_close_at)
+        })
+}
+
+async fn get_opened(Contest(contest): Contest) -> Json<GetOpenedResp> {
     let now = OffsetDateTime::now_utc();
-    let opened = now > CONFIG.voting_close_at;
+    let open_at = result_open_at(&contest);
     Json(GetOpenedResp {
-        opened,
-        open_at: CONFIG.voting_close_at,
+        opened: now > open_at,
+        open_at,
     })
 }
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
-struct WithVoteCount<M> {
+struct Standing {
+    /// 1-based rank, shared by submissions which are still tied after the tie-break.
+    rank: u64,
     #[serde(flatten)]
-    metadata: M,
-    vote_count: i64,
+    metadata: submission::Metadata,
+    vote_count: u64,
+    /// Tally under the contest voting method: number of votes, average score or Borda points.
+    public_points: f64,
+    jury_count: u64,
+    /// Average jury score, out of [`JURY_MAX_SCORE`].
+    jury_score: Option<f64>,
+    /// Public points alone without a jury weight, otherwise the weighted sum of public points
+    /// relative to the best submission and the jury score relative to its maximum, from 0 to 1.
+    points: f64,
 }
 
-impl<M> FromQueryResult for WithVoteCount<M>
-where
-    M: FromQueryResult,
-{
-    fn from_query_result(res: &sea_orm::QueryResult, pre: &str) -> Result<Self, migration::DbErr> {
-        let metadata = M::from_query_result(res, pre)?;
-        let vote_count = res.try_get(pre, "vote_count")?;
-        Ok(Self {
-            metadata,
-            vote_count,
-        })
-    }
+#[derive(Default)]
+struct Tally {
+    vote_count: u64,
+    points: f64,
+    /// ID of the vote which was cast last, as IDs are assigned in order.
+    last_vote_id: Option<i32>,
 }
 
-async fn get_literature(
-    extract::State(state): extract::State<AppState>,
-) -> Result<Json<Vec<WithVoteCount<literature::Metadata>>>, (StatusCode, &'static str)> {
-    if !CONFIG.literature_enabled {
-        return Err((StatusCode::BAD_REQUEST, "literature not enabled"));
+/// Vote as cast, for tallying.
+struct CastVote {
+    id: i32,
+    /// Account ID of the voter, or its handle for votes made before IDs were recorded, and its
+    /// instance.
+    voter: (String, String),
+    submission_id: i32,
+    score: Option<i32>,
+    rank: Option<i32>,
+}
+
+/// Tallies votes per listed submission. Votes cast without the value the method needs, e.g. before
+/// the method was changed, only count towards `vote_count`.
+///
+/// Votes are counted in the order they were cast, and the ones for submissions not listed, e.g.
+/// hidden ones, are left out. Repeated votes of a voter for a submission and votes past the
+/// `vote_limit` of a voter are not counted either, as concurrent requests can slip past the checks
+/// made on voting.
+fn tally(
+    method: VotingMethod,
+    vote_limit: u64,
+    listed: &HashSet<i32>,
+    mut votes: Vec<CastVote>,
+) -> HashMap<i32, Tally> {
+    let mut tallies = HashMap::<i32, Tally>::new();
+    let mut score_counts = HashMap::<i32, u64>::new();
+    let mut counted = HashSet::new();
+    let mut voter_counts = HashMap::<(String, String), u64>::new();
+
+    votes.sort_by_key(|vote| vote.id);
+    for vote in votes {
+        let CastVote {
+            id: vote_id,
+            voter,
+            submission_id,
+            score,
+            rank,
+        } = vote;
+        if !listed.contains(&submission_id) || counted.contains(&(voter.clone(), submission_id)) {
+            continue;
+        }
+        let voter_count = voter_counts.entry(voter.clone()).or_default();
+        if *voter_count >= vote_limit {
+            continue;
+        }
+        *voter_count += 1;
+        counted.insert((voter, submission_id));
+
+        let tally = tallies.entry(submission_id).or_default();
+        tally.vote_count += 1;
+        tally.last_vote_id = tally.last_vote_id.max(Some(vote_id));
+        match method {
+            VotingMethod::Approval => tally.points += 1.0,
+            VotingMethod::Score => {
+                if let Some(score) = score {
+                    tally.points += score as f64;
+                    *score_counts.entry(submission_id).or_default() += 1;
+                }
+            }
+            VotingMethod::Ranked => {
+                // Borda count over a full ballot of `vote_limit` picks, so the first pick
+                // earns `vote_limit` points and the last possible one earns a single point.
+                if let Some(rank) = rank {
+                    tally.points += (vote_limit as f64 - rank as f64 + 1.0).max(0.0);
+                }
+            }
+        }
+    }
+
+    if method == VotingMethod::Score {
+        for (submission_id, tally) in &mut tallies {
+            tally.points = match score_counts.get(submission_id) {
+                Some(&count) => tally.points / count as f64,
+                None => 0.0,
+            };
+        }
     }
 
+    tallies
+}
+
+async fn get_result(
+    ContestCategory(contest, category): ContestCategory,
+    extract::State(state): extract::State<AppState>,
+) -> Result<Json<Vec<Standing>>, (StatusCode, &'static str)> {
     let now = OffsetDateTime::now_utc();
-    if now <= CONFIG.voting_close_at {
+    if now <= result_open_at(&contest) {
         return Err((StatusCode::BAD_REQUEST, "voting not ended"));
     }
 
-    let literatures = literature::Metadata::select_cols(
-        literature::Entity::find()
-            .left_join(literature_vote::Entity)
-            .select_only(),
-    )
-    .column_as(literature_vote::Column::Id.count(), "vote_count")
-    .group_by(literature::Column::Id)
-    .order_by_desc(Expr::custom_keyword(Alias::new("vote_count")))
-    .order_by_asc(literature::Column::Id)
-    .into_model::<WithVoteCount<literature::Metadata>>()
-    .all(&*state.db)
-    .await
-    .map_err(|err| {
-        tracing::error!(?err, "failed to query database");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to query database",
+    let submissions = submission::Entity::find()
+        .filter(submission::Column::ContestId.eq(contest.id))
+        .filter(submission::Column::Category.eq(&category.slug))
+        .filter(submission::Column::IsHidden.eq(false))
+        .order_by_asc(submission::Column::Id)
+        .into_partial_model::<submission::AdminMetadata>()
+        .all(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?;
+
+    let votes = vote::Entity::find()
+        .select_only()
+        .column(vote::Column::Id)
+        .column(vote::Column::AccountId)
+        .column(vote::Column::Handle)
+        .column(vote::Column::Instance)
+        .column(vote::Column::SubmissionId)
+        .column(vote::Column::Score)
+        .column(vote::Column::Rank)
+        .filter(vote::Column::ContestId.eq(contest.id))
+        .filter(vote::Column::Category.eq(&category.slug))
+        .into_tuple::<(
+            i32,
+            Option<String>,
+            String,
+            String,
+            i32,
+            Option<i32>,
+            Option<i32>,
+        )>()
+        .all(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?
+        .into_iter()
+        .map(
+            |(id, account_id, handle, instance, submission_id, score, rank)| CastVote {
+                id,
+                voter: (account_id.unwrap_or(handle), instance),
+                submission_id,
+                score,
+                rank,
+            },
         )
-    })?;
+        .collect();
+
+    let jury_scores = jury_score::Entity::find()
+        .select_only()
+        .column(jury_score::Column::SubmissionId)
+        .column(jury_score::Column::Score)
+        .filter(jury_score::Column::ContestId.eq(contest.id))
+        .filter(jury_score::Column::Category.eq(&category.slug))
+        .into_tuple::<(i32, i32)>()
+        .all(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?;
+    let mut jury_totals = HashMap::<i32, (u64, i64)>::new();
+    for (submission_id, score) in jury_scores {
+        let (count, total) = jury_totals.entry(submission_id).or_default();
+        *count += 1;
+        *total += score as i64;
+    }
 
-    Ok(Json(literatures))
+    let listed = submissions.iter().map(|metadata| metadata.id).collect();
+    let mut tallies = tally(contest.voting_method, category.vote_limit, &listed, votes);
+    let submissions = submissions
+        .into_iter()
+        .map(|metadata| {
+            let tally = tallies.remove(&metadata.id).unwrap_or_default();
+            (metadata, tally)
+        })
+        .collect::<Vec<_>>();
+    // Only submissions in the standings count, so hidden ones do not set the scale.
+    let max_public_points = submissions
+        .iter()
+        .map(|(_, tally)| tally.points)
+        .fold(0.0, f64::max);
+    let standings = submissions
+        .into_iter()
+        .map(|(metadata, tally)| {
+            let (jury_count, jury_total) = jury_totals.remove(&metadata.id).unwrap_or_default();
+            let jury_score = (jury_count > 0).then(|| jury_total as f64 / jury_count as f64);
+            let points = if contest.jury_weight == 0.0 {
+                tally.points
+            } else {
+                weighted_points(
+                    tally.points,
+                    max_public_points,
+                    jury_score,
+                    contest.jury_weight,
+                )
+            };
+            let tie_break_key = match contest.tie_break {
+                TieBreak::Shared => None,
+                TieBreak::EarliestVote => tally.last_vote_id,
+                TieBreak::Judge => metadata.tie_break_priority,
+            };
+            (
+                Standing {
+                    rank: 0,
+                    metadata: metadata.into(),
+                    vote_count: tally.vote_count,
+                    public_points: tally.points,
+                    jury_count,
+                    jury_score,
+                    points,
+                },
+                points,
+                tie_break_key,
+            )
+        })
+        .collect();
+
+    Ok(Json(
+        rank(standings)
+            .into_iter()
+            .map(|(rank, standing)| Standing { rank, ..standing })
+            .collect(),
+    ))
 }
 
-async fn get_art(
-    extract::State(state): extract::State<AppState>,
-) -> Result<Json<Vec<WithVoteCount<art::Metadata>>>, (StatusCode, &'static str)> {
-    if !CONFIG.art_enabled {
-        return Err((StatusCode::BAD_REQUEST, "art not enabled"));
+/// Weighted sum of public points relative to the best submission and the jury score relative to
+/// its maximum, from 0 to 1.
+fn weighted_points(
+    public_points: f64,
+    max_public_points: f64,
+    jury_score: Option<f64>,
+    jury_weight: f64,
+) -> f64 {
+    let public_share = if max_public_points > 0.0 {
+        public_points / max_public_points
+    } else {
+        0.0
+    };
+    let jury_share = jury_score.unwrap_or(0.0) / JURY_MAX_SCORE as f64;
+    (1.0 - jury_weight) * public_share + jury_weight * jury_share
+}
+
+/// Orders `(entry, points, tie_break_key)` by points, then by tie-break key, and assigns 1-based
+/// ranks. Entries without a tie-break key come after the ones with it. Entries still tied share a
+/// rank and the ranks after them are skipped, and as the sort is stable, they keep their order.
+fn rank<T>(mut entries: Vec<(T, f64, Option<i32>)>) -> Vec<(u64, T)> {
+    entries.sort_by(|(_, a_points, a_key), (_, b_points, b_key)| {
+        b_points
+            .total_cmp(a_points)
+            .then_with(|| match (a_key, b_key) {
+                (Some(a_key), Some(b_key)) => a_key.cmp(b_key),
+                (a_key, b_key) => b_key.is_some().cmp(&a_key.is_some()),
+            })
+    });
+
+    let mut prev = None;
+    entries
+        .into_iter()
+        .enumerate()
+        .map(|(i, (entry, points, key))| {
+            let curr = (points, key);
+            let rank = match prev {
+                Some((prev_rank, prev_curr)) if prev_curr == curr => prev_rank,
+                _ => i as u64 + 1,
+            };
+            prev = Some((rank, curr));
+            (rank, entry)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote(id: i32, voter: &str, submission_id: i32) -> CastVote {
+        CastVote {
+            id,
+            voter: (voter.to_owned(), "example.com".to_owned()),
+            submission_id,
+            score: None,
+            rank: None,
+        }
     }
 
-    let now = OffsetDateTime::now_utc();
-    if now <= CONFIG.voting_close_at {
-        return Err((StatusCode::BAD_REQUEST, "voting not ended"));
+    fn points(tallies: &HashMap<i32, Tally>, submission_id: i32) -> f64 {
+        tallies
+            .get(&submission_id)
+            .map_or(0.0, |tally| tally.points)
     }
 
-    let arts = art::Metadata::select_cols(
-        art::Entity::find()
-            .left_join(art_vote::Entity)
-            .select_only(),
-    )
-    .column_as(art_vote::Column::Id.count(), "vote_count")
-    .group_by(art::Column::Id)
-    .order_by_desc(Expr::custom_keyword(Alias::new("vote_count")))
-    .order_by_asc(art::Column::Id)
-    .into_model::<WithVoteCount<art::Metadata>>()
-    .all(&*state.db)
-    .await
-    .map_err(|err| {
-        tracing::error!(?err, "failed to query database");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to query database",
-        )
-    })?;
+    #[test]
+    fn tally_counts_repeated_votes_once() {
+        let listed = HashSet::from([1, 2]);
+        let votes = vec![vote(1, "a", 1), vote(2, "a", 1), vote(3, "b", 1)];
+        let tallies = tally(VotingMethod::Approval, 3, &listed, votes);
+        assert_eq!(tallies[&1].vote_count, 2);
+        assert_eq!(points(&tallies, 1), 2.0);
+        assert_eq!(tallies[&1].last_vote_id, Some(3));
+    }
+
+    #[test]
+    fn tally_leaves_out_unlisted_submissions() {
+        // Submission 2 is hidden and 3 was withdrawn, so neither is listed.
+        let listed = HashSet::from([1]);
+        let votes = vec![vote(1, "a", 2), vote(2, "a", 3), vote(3, "a", 1)];
+        let tallies = tally(VotingMethod::Approval, 1, &listed, votes);
+        assert_eq!(tallies.len(), 1);
+        assert_eq!(tallies[&1].vote_count, 1);
+    }
+
+    #[test]
+    fn tally_caps_votes_per_voter() {
+        let listed = HashSet::from([1, 2, 3]);
+        // Out of order, as rows are not read in any particular order.
+        let votes = vec![
+            vote(3, "a", 3),
+            vote(1, "a", 1),
+            vote(2, "a", 2),
+            vote(4, "b", 3),
+        ];
+        let tallies = tally(VotingMethod::Approval, 2, &listed, votes);
+        assert_eq!(points(&tallies, 1), 1.0);
+        assert_eq!(points(&tallies, 2), 1.0);
+        assert_eq!(points(&tallies, 3), 1.0);
+    }
+
+    #[test]
+    fn tally_tells_voters_apart_by_instance() {
+        let listed = HashSet::from([1]);
+        let mut other = vote(2, "a", 1);
+        other.voter.1 = "example.net".to_owned();
+        let tallies = tally(
+            VotingMethod::Approval,
+            1,
+            &listed,
+            vec![vote(1, "a", 1), other],
+        );
+        assert_eq!(tallies[&1].vote_count, 2);
+    }
 
-    Ok(Json(arts))
+    #[test]
+    fn tally_averages_scores() {
+        let listed = HashSet::from([1]);
+        let votes = vec![
+            CastVote {
+                score: Some(4),
+                ..vote(1, "a", 1)
+            },
+            CastVote {
+                score: Some(2),
+                ..vote(2, "b", 1)
+            },
+            // Cast before the method was changed.
+            vote(3, "c", 1),
+        ];
+        let tallies = tally(VotingMethod::Score, 1, &listed, votes);
+        assert_eq!(tallies[&1].vote_count, 3);
+        assert_eq!(points(&tallies, 1), 3.0);
+    }
+
+    #[test]
+    fn tally_borda_counts_ranks() {
+        let listed = HashSet::from([1, 2, 3]);
+        let ranked = |id, voter, submission_id, rank| CastVote {
+            rank: Some(rank),
+            ..vote(id, voter, submission_id)
+        };
+        let votes = vec![
+            ranked(1, "a", 1, 1),
+            ranked(2, "a", 2, 2),
+            ranked(3, "b", 2, 1),
+            ranked(4, "b", 1, 2),
+            // Past the limit of two picks.
+            ranked(5, "b", 3, 3),
+        ];
+        let tallies = tally(VotingMethod::Ranked, 2, &listed, votes);
+        assert_eq!(points(&tallies, 1), 3.0);
+        assert_eq!(points(&tallies, 2), 3.0);
+        assert_eq!(points(&tallies, 3), 0.0);
+    }
+
+    #[test]
+    fn rank_shares_ties_and_skips_after_them() {
+        let ranks = rank(vec![
+            ("c", 1.0, None),
+            ("a", 3.0, None),
+            ("b", 3.0, None),
+            ("d", 0.0, None),
+        ]);
+        assert_eq!(ranks, [(1, "a"), (1, "b"), (3, "c"), (4, "d")]);
+    }
+
+    #[test]
+    fn rank_breaks_ties_by_key() {
+        let ranks = rank(vec![
+            ("a", 2.0, None),
+            ("b", 2.0, Some(5)),
+            ("c", 2.0, Some(3)),
+            ("d", 2.0, Some(3)),
+        ]);
+        assert_eq!(ranks, [(1, "c"), (1, "d"), (3, "b"), (4, "a")]);
+    }
+
+    #[test]
+    fn rank_all_zero() {
+        let ranks = rank(vec![("a", 0.0, None), ("b", 0.0, None), ("c", 0.0, None)]);
+        assert_eq!(ranks, [(1, "a"), (1, "b"), (1, "c")]);
+    }
+
+    #[test]
+    fn rank_single() {
+        assert_eq!(rank(vec![("a", 0.0, Some(1))]), [(1, "a")]);
+    }
+
+    #[test]
+    fn rank_weighs_jury_scores() {
+        let max = 10.0;
+        let jury_max = Some(JURY_MAX_SCORE as f64);
+        let ranks = rank(vec![
+            // Most public votes, but no jury score.
+            ("a", weighted_points(10.0, max, None, 0.6), None),
+            ("b", weighted_points(5.0, max, jury_max, 0.6), None),
+            ("c", weighted_points(0.0, max, jury_max, 0.6), None),
+        ]);
+        assert_eq!(ranks, [(1, "b"), (2, "c"), (3, "a")]);
+        assert_eq!(weighted_points(10.0, max, jury_max, 0.6), 1.0);
+        assert_eq!(weighted_points(0.0, 0.0, None, 0.5), 0.0);
+    }
 }
@@ -1,71 +1,86 @@
-use std::io::Cursor;
+use std::io::{BufWriter, Write};
 
 use axum::{
-    extract::{self, Multipart},
-    http::StatusCode,
+    body::Body,
+    extract::{self, multipart::Field, FromRequest, Multipart},
+    http::{Request, StatusCode},
     routing, Json, Router,
 };
+use memmap2::Mmap;
 use sea_orm::{
-    ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter,
-    TransactionTrait,
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, EntityTrait, ModelTrait,
+    PaginatorTrait, QueryFilter, TransactionTrait,
 };
 use serde::Deserialize;
-use thumbnailer::{create_thumbnails, ThumbnailSize};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
 use time::OffsetDateTime;
+use tokio::io::AsyncWriteExt;
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::{
-    config::CONFIG,
-    entity::{art, literature},
+    config::{Category, CategoryKind, CONFIG},
+    entity::{
+        contest,
+        submission::{self, TextFormat},
+        submission_derivative, submission_duplicate, submission_page,
+    },
     handler::{api::oauth::User, AppState},
-    misskey::post_note,
+    job::{self, Job},
+    media::{self, ImageFormat},
+    storage, text,
 };
 
-use super::GetOpenedResp;
+use super::{
+    discard_images, find_image_keys, lock_user_rows, Contest, ContestCategory, GetOpenedResp,
+    IdPath,
+};
 
 pub(super) fn create_router() -> Router<AppState> {
     Router::new()
         .route("/opened", routing::get(get_opened))
-        .route("/literature", routing::post(post_literature))
-        .route("/art", routing::post(post_art))
+        .route("/:category", routing::post(post_submission))
+        .route(
+            "/:category/:id",
+            routing::put(put_submission).delete(delete_submission),
+        )
 }
 
-async fn get_opened() -> Json<GetOpenedResp> {
+async fn get_opened(Contest(contest): Contest) -> Json<GetOpenedResp> {
     let now = OffsetDateTime::now_utc();
-    let opened = now >= CONFIG.submission_open_at && now <= CONFIG.submission_close_at;
+    let opened = now >= contest.submission_open_at && now <= contest.submission_close_at;
     Json(GetOpenedResp {
         opened,
-        open_at: CONFIG.submission_open_at,
-        close_at: CONFIG.submission_close_at,
+        open_at: contest.submission_open_at,
+        close_at: contest.submission_close_at,
     })
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct PostLiteratureReq {
-    title: String,
-    text: String,
-    is_nsfw: bool,
-}
-
-async fn post_literature(
+async fn post_submission(
     user: User,
+    ContestCategory(contest, category): ContestCategory,
     extract::State(state): extract::State<AppState>,
-    Json(req): Json<PostLiteratureReq>,
-) -> Result<Json<literature::Model>, (StatusCode, &'static str)> {
-    if !CONFIG.literature_enabled {
-        return Err((StatusCode::BAD_REQUEST, "literature not enabled"));
-    }
-
-    if req.title.graphemes(true).count() > 100 || req.text.graphemes(true).count() > 7000 {
-        return Err((StatusCode::BAD_REQUEST, "too long text"));
-    }
+    req: Request<Body>,
+) -> Result<Json<submission::Metadata>, (StatusCode, &'static str)> {
+    let SubmissionForm {
+        title,
+        description,
+        is_nsfw,
+        text,
+        text_format,
+        images,
+        alt_texts,
+    } = read_submission_form(category, req, &state).await?;
 
     let now = OffsetDateTime::now_utc();
-    if now < CONFIG.submission_open_at || now > CONFIG.submission_close_at {
+    if now < contest.submission_open_at || now > contest.submission_close_at {
         return Err((StatusCode::BAD_REQUEST, "submission not available"));
     }
 
+    if category.kind == CategoryKind::Image && images.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "data not found"));
+    }
+
     let tx = state.db.begin().await.map_err(|err| {
         tracing::error!(?err, "failed to begin transaction");
         (
@@ -74,11 +89,14 @@ async fn post_literature(
         )
     })?;
 
-    let existing_literature = literature::Entity::find()
+    lock_user_rows(&tx, "submission", &contest, category, &user).await?;
+    let existing_submission = submission::Entity::find()
         .filter(
-            literature::Column::AuthorHandle
-                .eq(&user.handle)
-                .and(literature::Column::AuthorInstance.eq(&user.instance)),
+            submission::Column::ContestId
+                .eq(contest.id)
+                .and(submission::Column::Category.eq(&category.slug))
+                .and(submission::Column::AuthorAccountId.eq(&user.account_id))
+                .and(submission::Column::AuthorInstance.eq(&user.instance)),
         )
         .count(&tx)
         .await
@@ -89,24 +107,310 @@ async fn post_literature(
                 "failed to query database",
             )
         })?;
-    if existing_literature > 0 {
+    if existing_submission >= category.submission_limit {
         return Err((StatusCode::CONFLICT, "already submitted user"));
     }
 
-    let literature_activemodel = literature::ActiveModel {
+    let stored_images = store_images(&state, images).await?;
+    let first_image = stored_images.first();
+
+    let submission_activemodel = submission::ActiveModel {
         id: ActiveValue::NotSet,
-        title: ActiveValue::Set(req.title),
-        text: ActiveValue::Set(req.text),
+        contest_id: ActiveValue::Set(contest.id),
+        category: ActiveValue::Set(category.slug.clone()),
+        title: ActiveValue::Set(title),
+        description: ActiveValue::Set(description),
+        text: ActiveValue::Set(text),
+        data: ActiveValue::Set(None),
+        thumbnail_data: ActiveValue::Set(None),
         author_handle: ActiveValue::Set(user.handle),
         author_instance: ActiveValue::Set(user.instance),
-        is_nsfw: ActiveValue::Set(req.is_nsfw),
+        author_account_id: ActiveValue::Set(Some(user.account_id)),
+        is_nsfw: ActiveValue::Set(is_nsfw),
+        is_hidden: ActiveValue::Set(false),
+        tie_break_priority: ActiveValue::Set(None),
+        data_key: ActiveValue::Set(first_image.map(|image| image.data_key.clone())),
+        thumbnail_key: ActiveValue::Set(None),
+        mime_type: ActiveValue::Set(first_image.map(|image| image.mime_type.clone())),
+        data_hash: ActiveValue::Set(first_image.map(|image| image.data_hash.clone())),
+        thumbnail_hash: ActiveValue::Set(None),
+        private_data_key: ActiveValue::Set(
+            first_image.and_then(|image| image.private_data_key.clone()),
+        ),
+        alt_texts: ActiveValue::Set(alt_texts),
+        perceptual_hashes: ActiveValue::Set(Vec::new()),
+        text_format: ActiveValue::Set(text_format),
     };
 
-    let literature = literature_activemodel.insert(&tx).await.map_err(|err| {
-        tracing::error!(?err, "failed to insert to database");
+    let submission = async {
+        let submission = submission_activemodel.insert(&tx).await.map_err(|err| {
+            tracing::error!(?err, "failed to insert to database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to insert to database",
+            )
+        })?;
+        insert_images(&tx, submission.id, &stored_images).await?;
+        enqueue_announcement(&tx, &contest, category, &submission).await?;
+
+        tx.commit().await.map_err(|err| {
+            tracing::error!(?err, "failed to commit to database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to commit to database",
+            )
+        })?;
+
+        Ok(submission)
+    }
+    .await;
+    let submission = match submission {
+        Ok(submission) => submission,
+        Err(err) => {
+            discard_images(
+                &*state.storage,
+                stored_images.into_iter().flat_map(StoredImage::into_keys),
+            )
+            .await;
+            return Err(err);
+        }
+    };
+
+    Ok(Json(submission.into()))
+}
+
+/// Enqueues announcing the new submission on Misskey, if configured.
+async fn enqueue_announcement<C>(
+    conn: &C,
+    contest: &contest::Model,
+    category: &Category,
+    submission: &submission::Model,
+) -> Result<(), (StatusCode, &'static str)>
+where
+    C: ConnectionTrait,
+{
+    if CONFIG.misskey_base_url.is_none() || CONFIG.misskey_api_key.is_none() {
+        return Ok(());
+    }
+    let url = match CONFIG.base_url.join(&format!(
+        "./{}/{}/{}",
+        contest.slug, category.slug, submission.id
+    )) {
+        Ok(url) => url,
+        Err(err) => {
+            tracing::warn!(?err, "failed to join submission URL");
+            return Ok(());
+        }
+    };
+    let text = format!(
+        "**{}**의 {} 부문에 새 작품이 등록되었어요!\n> {}{}\n{}보러가기: {}",
+        contest.name,
+        category.name,
+        if contest.blind {
+            String::new()
+        } else {
+            format!(
+                "{}@{} - ",
+                submission.author_handle, submission.author_instance
+            )
+        },
+        submission.title,
+        if submission.is_nsfw {
+            "**!!!NSFW!!!**\n"
+        } else {
+            ""
+        },
+        url
+    );
+
+    job::enqueue(conn, &Job::PostNote { text })
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to enqueue job");
+            (StatusCode::INTERNAL_SERVER_ERROR, "failed to enqueue job")
+        })
+}
+
+async fn find_own_submission<C>(
+    conn: &C,
+    contest_id: i32,
+    category: &Category,
+    id: i32,
+    user: &User,
+) -> Result<submission::Model, (StatusCode, &'static str)>
+where
+    C: ConnectionTrait,
+{
+    let submission = submission::Entity::find_by_id(id)
+        .filter(submission::Column::ContestId.eq(contest_id))
+        .filter(submission::Column::Category.eq(&category.slug))
+        .one(conn)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "submission not found"))?;
+
+    if submission.author_account_id.as_ref() != Some(&user.account_id)
+        || submission.author_instance != user.instance
+    {
+        return Err((StatusCode::FORBIDDEN, "not author"));
+    }
+
+    Ok(submission)
+}
+
+async fn put_submission(
+    user: User,
+    ContestCategory(contest, category): ContestCategory,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
+    extract::State(state): extract::State<AppState>,
+    req: Request<Body>,
+) -> Result<Json<submission::Metadata>, (StatusCode, &'static str)> {
+    let SubmissionForm {
+        title,
+        description,
+        is_nsfw,
+        text,
+        text_format,
+        images,
+        alt_texts,
+    } = read_submission_form(category, req, &state).await?;
+
+    let now = OffsetDateTime::now_utc();
+    if now < contest.submission_open_at || now > contest.submission_close_at {
+        return Err((StatusCode::BAD_REQUEST, "submission not available"));
+    }
+
+    let tx = state.db.begin().await.map_err(|err| {
+        tracing::error!(?err, "failed to begin transaction");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to begin transaction",
+        )
+    })?;
+
+    let submission = find_own_submission(&tx, contest.id, category, id, &user).await?;
+    let old_keys = [
+        submission.data_key.clone(),
+        submission.thumbnail_key.clone(),
+        submission.private_data_key.clone(),
+    ];
+    let old_image_keys = find_image_keys(&tx, submission.id).await?;
+    if images.is_empty() && !alt_texts.is_empty() && alt_texts.len() != submission.alt_texts.len() {
+        return Err((StatusCode::BAD_REQUEST, "alt text count mismatch"));
+    }
+
+    let mut submission_activemodel: submission::ActiveModel = submission.into();
+    submission_activemodel.title = ActiveValue::Set(title);
+    submission_activemodel.description = ActiveValue::Set(description);
+    submission_activemodel.is_nsfw = ActiveValue::Set(is_nsfw);
+    if text.is_some() {
+        submission_activemodel.text = ActiveValue::Set(text);
+        submission_activemodel.text_format = ActiveValue::Set(text_format);
+    }
+    if !alt_texts.is_empty() {
+        submission_activemodel.alt_texts = ActiveValue::Set(alt_texts);
+    }
+    // Uploading any image replaces all pages.
+    let images_replaced = !images.is_empty();
+    if images_replaced {
+        submission_activemodel.perceptual_hashes = ActiveValue::Set(Vec::new());
+    }
+    let stored_images = store_images(&state, images).await?;
+    if let Some(image) = stored_images.first() {
+        submission_activemodel.data = ActiveValue::Set(None);
+        submission_activemodel.thumbnail_data = ActiveValue::Set(None);
+        submission_activemodel.data_key = ActiveValue::Set(Some(image.data_key.clone()));
+        submission_activemodel.thumbnail_key = ActiveValue::Set(None);
+        submission_activemodel.mime_type = ActiveValue::Set(Some(image.mime_type.clone()));
+        submission_activemodel.data_hash = ActiveValue::Set(Some(image.data_hash.clone()));
+        submission_activemodel.thumbnail_hash = ActiveValue::Set(None);
+        submission_activemodel.private_data_key = ActiveValue::Set(image.private_data_key.clone());
+    }
+
+    let submission = async {
+        let submission = submission_activemodel.update(&tx).await.map_err(|err| {
+            tracing::error!(?err, "failed to update database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to update database",
+            )
+        })?;
+        if images_replaced {
+            delete_images(&tx, submission.id).await?;
+            insert_images(&tx, submission.id, &stored_images).await?;
+        }
+
+        tx.commit().await.map_err(|err| {
+            tracing::error!(?err, "failed to commit to database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to commit to database",
+            )
+        })?;
+
+        Ok(submission)
+    }
+    .await;
+    let submission = match submission {
+        Ok(submission) => submission,
+        Err(err) => {
+            discard_images(
+                &*state.storage,
+                stored_images.into_iter().flat_map(StoredImage::into_keys),
+            )
+            .await;
+            return Err(err);
+        }
+    };
+    if images_replaced {
+        discard_images(
+            &*state.storage,
+            old_keys.into_iter().flatten().chain(old_image_keys),
+        )
+        .await;
+    }
+
+    Ok(Json(submission.into()))
+}
+
+async fn delete_submission(
+    user: User,
+    ContestCategory(contest, category): ContestCategory,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
+    extract::State(state): extract::State<AppState>,
+) -> Result<(), (StatusCode, &'static str)> {
+    let now = OffsetDateTime::now_utc();
+    if now < contest.submission_open_at || now > contest.submission_close_at {
+        return Err((StatusCode::BAD_REQUEST, "submission not available"));
+    }
+
+    let tx = state.db.begin().await.map_err(|err| {
+        tracing::error!(?err, "failed to begin transaction");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to begin transaction",
+        )
+    })?;
+
+    let submission = find_own_submission(&tx, contest.id, category, id, &user).await?;
+    let keys = [
+        submission.data_key.clone(),
+        submission.thumbnail_key.clone(),
+        submission.private_data_key.clone(),
+    ];
+    let image_keys = find_image_keys(&tx, submission.id).await?;
+
+    submission.delete(&tx).await.map_err(|err| {
+        tracing::error!(?err, "failed to delete from database");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to insert to database",
+            "failed to delete from database",
         )
     })?;
 
@@ -118,53 +422,198 @@ async fn post_literature(
         )
     })?;
 
-    match CONFIG
-        .base_url
-        .join(&format!("./literature/{}", literature.id))
-    {
-        Ok(url) => {
-            if let Err(err) = post_note(
-                &state.http_client,
-                format!(
-                    "**{}**에 새 글이 등록되었어요!\n> {}@{} - {}\n{}보러가기: {}",
-                    CONFIG.contest_name,
-                    literature.author_handle,
-                    literature.author_instance,
-                    literature.title,
-                    if literature.is_nsfw {
-                        "**!!!NSFW!!!**\n"
-                    } else {
-                        ""
-                    },
-                    url
-                ),
-            )
-            .await
-            {
-                tracing::warn!(?err, "failed to post note");
+    discard_images(
+        &*state.storage,
+        keys.into_iter().flatten().chain(image_keys),
+    )
+    .await;
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TextSubmissionReq {
+    title: String,
+    #[serde(default)]
+    description: String,
+    text: String,
+    #[serde(default)]
+    format: TextFormat,
+    is_nsfw: bool,
+}
+
+struct SubmissionForm {
+    title: String,
+    description: String,
+    is_nsfw: bool,
+    text: Option<String>,
+    text_format: Option<TextFormat>,
+    /// Images in page order.
+    images: Vec<FormImage>,
+    /// Alt text of every image. Given without images, they replace the alt texts of the current
+    /// images.
+    alt_texts: Vec<String>,
+}
+
+/// Uploaded image, kept in temporary files rather than in memory until it is stored.
+struct FormImage {
+    /// Image with its metadata stripped.
+    file: NamedTempFile,
+    hash: String,
+    /// Image as uploaded, if private originals are kept.
+    private_file: Option<NamedTempFile>,
+    format: ImageFormat,
+}
+
+/// Reads a JSON body for text categories and multipart form data for image categories, and
+/// checks it against the category limits. Markdown texts are counted without their markup.
+async fn read_submission_form(
+    category: &'static Category,
+    req: Request<Body>,
+    state: &AppState,
+) -> Result<SubmissionForm, (StatusCode, &'static str)> {
+    let form = match category.kind {
+        CategoryKind::Text => {
+            let Json(req) = Json::<TextSubmissionReq>::from_request(req, state)
+                .await
+                .map_err(|_| (StatusCode::BAD_REQUEST, "invalid request body"))?;
+            SubmissionForm {
+                title: req.title,
+                description: req.description,
+                is_nsfw: req.is_nsfw,
+                text: Some(req.text),
+                text_format: Some(req.format),
+                images: Vec::new(),
+                alt_texts: Vec::new(),
             }
         }
-        Err(err) => {
-            tracing::warn!(?err, "failed to join literature URL");
+        CategoryKind::Image => {
+            let mut req = Multipart::from_request(req, state)
+                .await
+                .map_err(|_| (StatusCode::BAD_REQUEST, "invalid request body"))?;
+            read_image_form(&mut req, category).await?
         }
+    };
+
+    if form.title.graphemes(true).count() > category.title_max_length
+        || form.description.graphemes(true).count() > category.description_max_length
+        || form
+            .text
+            .as_ref()
+            .zip(form.text_format)
+            .is_some_and(|(text, format)| {
+                text::text_content(text, format).graphemes(true).count() > category.text_max_length
+            })
+        || form
+            .alt_texts
+            .iter()
+            .any(|alt_text| alt_text.graphemes(true).count() > category.alt_text_max_length)
+    {
+        return Err((StatusCode::BAD_REQUEST, "too long text"));
     }
 
-    Ok(Json(literature))
+    Ok(form)
 }
 
-async fn post_art(
-    user: User,
-    extract::State(state): extract::State<AppState>,
-    mut req: Multipart,
-) -> Result<Json<art::Metadata>, (StatusCode, &'static str)> {
-    if !CONFIG.art_enabled {
-        return Err((StatusCode::BAD_REQUEST, "art not enabled"));
+fn temp_file_error(err: std::io::Error) -> (StatusCode, &'static str) {
+    tracing::error!(?err, "failed to access temporary file");
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "failed to access temporary file",
+    )
+}
+
+/// Streams a multipart field to a temporary file, failing as soon as it exceeds the size.
+async fn read_field_to_file(
+    mut field: Field<'_>,
+    max_size: usize,
+) -> Result<NamedTempFile, (StatusCode, &'static str)> {
+    let temp_file = NamedTempFile::new().map_err(temp_file_error)?;
+    let mut file = tokio::fs::File::from_std(temp_file.reopen().map_err(temp_file_error)?);
+
+    let mut size = 0;
+    while let Some(chunk) = field.chunk().await.map_err(|err| {
+        tracing::error!(?err, "failed to read from multipart field");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to read from multipart field",
+        )
+    })? {
+        size += chunk.len();
+        if size > max_size {
+            return Err((StatusCode::BAD_REQUEST, "too large image"));
+        }
+        file.write_all(&chunk).await.map_err(temp_file_error)?;
     }
+    file.flush().await.map_err(temp_file_error)?;
+
+    Ok(temp_file)
+}
 
+/// Checks an uploaded image against the category limits, identifies it by its content, and
+/// writes a copy with its metadata stripped. The upload is memory-mapped rather than read, so
+/// only the parts of it that are looked at are paged in, and they can be evicted again. It is
+/// not decoded, which is left to jobs.
+async fn read_form_image(
+    category: &'static Category,
+    temp_file: NamedTempFile,
+) -> Result<FormImage, (StatusCode, &'static str)> {
+    tokio::task::spawn_blocking(move || {
+        // SAFETY: The temporary file is private to this request and nothing writes to it while
+        // it is mapped, so the mapped bytes do not change under the borrows below.
+        let data = unsafe { Mmap::map(temp_file.as_file()) }.map_err(temp_file_error)?;
+
+        let image_format = ImageFormat::sniff(&data).ok_or((
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "unsupported image format",
+        ))?;
+        let (width, height) = media::image_dimensions(&data, image_format)
+            .map_err(|_| (StatusCode::BAD_REQUEST, "invalid image"))?;
+        if width > category.image_max_width || height > category.image_max_height {
+            return Err((StatusCode::BAD_REQUEST, "too large image dimensions"));
+        }
+        let stripped = media::strip_metadata(&data, image_format).map_err(|err| {
+            tracing::debug!(?err, "failed to strip image metadata");
+            (StatusCode::BAD_REQUEST, "invalid image")
+        })?;
+
+        let mut file = NamedTempFile::new().map_err(temp_file_error)?;
+        let mut writer = BufWriter::new(file.as_file_mut());
+        let mut hasher = Sha256::new();
+        for piece in stripped {
+            hasher.update(&piece);
+            writer.write_all(&piece).map_err(temp_file_error)?;
+        }
+        writer.flush().map_err(temp_file_error)?;
+        drop(writer);
+        drop(data);
+
+        Ok(FormImage {
+            file,
+            hash: hex::encode(hasher.finalize()),
+            private_file: CONFIG.keep_private_originals.then_some(temp_file),
+            format: image_format,
+        })
+    })
+    .await
+    .map_err(|err| {
+        tracing::error!(?err, "failed to process image");
+        (StatusCode::INTERNAL_SERVER_ERROR, "failed to process image")
+    })?
+}
+
+/// Images are read from repeated `data` fields in page order, and their alt texts from repeated
+/// `altText` fields in the same order. Images without one get an empty alt text.
+async fn read_image_form(
+    req: &mut Multipart,
+    category: &'static Category,
+) -> Result<SubmissionForm, (StatusCode, &'static str)> {
     let mut title = None;
     let mut description = None;
     let mut is_nsfw = None;
-    let mut data = None;
+    let mut images = Vec::new();
+    let mut alt_texts = Vec::new();
 
     while let Some(field) = req.next_field().await.map_err(|err| {
         tracing::error!(?err, "failed to read from multipart data");
@@ -204,7 +653,16 @@ async fn post_art(
                 })? == "true",
             );
         } else if name == "data" {
-            data = Some(field.bytes().await.map_err(|err| {
+            if images.len() as u64 >= category.page_limit {
+                return Err((StatusCode::BAD_REQUEST, "too many images"));
+            }
+            let temp_file = read_field_to_file(field, category.image_max_size).await?;
+            images.push(read_form_image(category, temp_file).await?);
+        } else if name == "altText" {
+            if alt_texts.len() as u64 >= category.page_limit {
+                return Err((StatusCode::BAD_REQUEST, "too many alt texts"));
+            }
+            alt_texts.push(field.text().await.map_err(|err| {
                 tracing::error!(?err, "failed to read from multipart field");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
@@ -217,128 +675,200 @@ async fn post_art(
     let title = title.ok_or((StatusCode::BAD_REQUEST, "title not found"))?;
     let description = description.ok_or((StatusCode::BAD_REQUEST, "description not found"))?;
     let is_nsfw = is_nsfw.ok_or((StatusCode::BAD_REQUEST, "isNsfw not found"))?;
-    let data = data.ok_or((StatusCode::BAD_REQUEST, "data not found"))?;
-
-    if title.graphemes(true).count() > 100 || description.graphemes(true).count() > 2000 {
-        return Err((StatusCode::BAD_REQUEST, "too long text"));
+    if !images.is_empty() {
+        if alt_texts.len() > images.len() {
+            return Err((StatusCode::BAD_REQUEST, "too many alt texts"));
+        }
+        alt_texts.resize(images.len(), String::new());
     }
 
-    if data.len() > 1024 * 1024 * 50 {
-        return Err((StatusCode::BAD_REQUEST, "too large image"));
+    Ok(SubmissionForm {
+        title,
+        description,
+        is_nsfw,
+        text: None,
+        text_format: None,
+        images,
+        alt_texts,
+    })
+}
+
+/// Image of a page in the storage backend. Its derivatives are generated by a job.
+struct StoredImage {
+    page: i32,
+    data_key: String,
+    mime_type: String,
+    data_hash: String,
+    private_data_key: Option<String>,
+}
+
+impl StoredImage {
+    fn into_keys(self) -> impl Iterator<Item = String> {
+        std::iter::once(self.data_key).chain(self.private_data_key)
     }
+}
 
-    let now = OffsetDateTime::now_utc();
-    if now < CONFIG.submission_open_at || now > CONFIG.submission_close_at {
-        return Err((StatusCode::BAD_REQUEST, "submission not available"));
+/// Puts every image to the storage backend as the pages from 1, all or none of them.
+async fn store_images(
+    state: &AppState,
+    images: Vec<FormImage>,
+) -> Result<Vec<StoredImage>, (StatusCode, &'static str)> {
+    let mut stored_images = Vec::with_capacity(images.len());
+    for (page, image) in (1..).zip(images) {
+        match store_image(state, page, image).await {
+            Ok(stored_image) => stored_images.push(stored_image),
+            Err(err) => {
+                discard_images(
+                    &*state.storage,
+                    stored_images.into_iter().flat_map(StoredImage::into_keys),
+                )
+                .await;
+                return Err(err);
+            }
+        }
     }
+    Ok(stored_images)
+}
 
-    let tx = state.db.begin().await.map_err(|err| {
-        tracing::error!(?err, "failed to begin transaction");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to begin transaction",
-        )
-    })?;
+/// Puts an image and its private original if any to the storage backend.
+async fn store_image(
+    state: &AppState,
+    page: i32,
+    image: FormImage,
+) -> Result<StoredImage, (StatusCode, &'static str)> {
+    let mut stored_image = StoredImage {
+        page,
+        data_key: storage::new_image_key("original"),
+        mime_type: image.format.mime().to_string(),
+        data_hash: image.hash,
+        private_data_key: None,
+    };
 
-    let existing_art = art::Entity::find()
-        .filter(
-            art::Column::AuthorHandle
-                .eq(&user.handle)
-                .and(art::Column::AuthorInstance.eq(&user.instance)),
-        )
-        .count(&tx)
+    state
+        .storage
+        .put_file(&stored_image.data_key, image.file.path())
         .await
         .map_err(|err| {
-            tracing::error!(?err, "failed to query database");
+            tracing::error!(?err, "failed to write to storage");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "failed to query database",
+                "failed to write to storage",
             )
         })?;
-    if existing_art > 0 {
-        return Err((StatusCode::CONFLICT, "already submitted user"));
+    if let Some(private_file) = &image.private_file {
+        let private_data_key = storage::new_image_key("private");
+        if let Err(err) = state
+            .storage
+            .put_file(&private_data_key, private_file.path())
+            .await
+        {
+            tracing::error!(?err, "failed to write to storage");
+            discard_images(&*state.storage, stored_image.into_keys()).await;
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to write to storage",
+            ));
+        }
+        stored_image.private_data_key = Some(private_data_key);
     }
 
-    let mut thumbnails = create_thumbnails(
-        Cursor::new(data.clone()),
-        mime::IMAGE_PNG,
-        [ThumbnailSize::Medium],
-    )
-    .map_err(|err| {
-        tracing::error!(?err, "failed to generate thumbnail");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to generate thumbnail",
-        )
-    })?;
-    let thumbnail = thumbnails.pop().unwrap();
+    Ok(stored_image)
+}
 
-    let mut thumbnail_data = Cursor::new(Vec::new());
-    thumbnail.write_png(&mut thumbnail_data).map_err(|err| {
-        tracing::error!(?err, "failed to write thumbnail data");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to write thumbnail data",
-        )
-    })?;
+/// Records the pages after the first, whose image is kept in the submission itself, and
+/// enqueues generating the derivatives of all pages and hashing them to find duplicates.
+async fn insert_images<C>(
+    conn: &C,
+    submission_id: i32,
+    stored_images: &[StoredImage],
+) -> Result<(), (StatusCode, &'static str)>
+where
+    C: ConnectionTrait,
+{
+    let pages = stored_images
+        .iter()
+        .skip(1)
+        .map(|image| submission_page::ActiveModel {
+            id: ActiveValue::NotSet,
+            submission_id: ActiveValue::Set(submission_id),
+            page: ActiveValue::Set(image.page),
+            data_key: ActiveValue::Set(image.data_key.clone()),
+            mime_type: ActiveValue::Set(image.mime_type.clone()),
+            data_hash: ActiveValue::Set(image.data_hash.clone()),
+            private_data_key: ActiveValue::Set(image.private_data_key.clone()),
+        })
+        .collect::<Vec<_>>();
+    if !pages.is_empty() {
+        submission_page::Entity::insert_many(pages)
+            .exec(conn)
+            .await
+            .map_err(|err| {
+                tracing::error!(?err, "failed to insert to database");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed to insert to database",
+                )
+            })?;
+    }
 
-    let art_activemodel = art::ActiveModel {
-        id: ActiveValue::NotSet,
-        title: ActiveValue::Set(title),
-        data: ActiveValue::Set(data.to_vec()),
-        thumbnail_data: ActiveValue::Set(thumbnail_data.into_inner()),
-        author_handle: ActiveValue::Set(user.handle),
-        author_instance: ActiveValue::Set(user.instance),
-        description: ActiveValue::Set(description),
-        is_nsfw: ActiveValue::Set(is_nsfw),
-    };
+    if !stored_images.is_empty() {
+        for job in [
+            Job::GenerateDerivatives { submission_id },
+            Job::HashImages { submission_id },
+        ] {
+            job::enqueue(conn, &job).await.map_err(|err| {
+                tracing::error!(?err, "failed to enqueue job");
+                (StatusCode::INTERNAL_SERVER_ERROR, "failed to enqueue job")
+            })?;
+        }
+    }
 
-    let art = art_activemodel.insert(&tx).await.map_err(|err| {
-        tracing::error!(?err, "failed to insert to database");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to insert to database",
-        )
-    })?;
+    Ok(())
+}
 
-    tx.commit().await.map_err(|err| {
-        tracing::error!(?err, "failed to commit to database");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to commit to database",
+/// Removes the records of all pages after the first, of all derivatives and of the duplicates
+/// found among the images.
+async fn delete_images<C>(conn: &C, submission_id: i32) -> Result<(), (StatusCode, &'static str)>
+where
+    C: ConnectionTrait,
+{
+    submission_page::Entity::delete_many()
+        .filter(submission_page::Column::SubmissionId.eq(submission_id))
+        .exec(conn)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to delete from database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to delete from database",
+            )
+        })?;
+    submission_derivative::Entity::delete_many()
+        .filter(submission_derivative::Column::SubmissionId.eq(submission_id))
+        .exec(conn)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to delete from database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to delete from database",
+            )
+        })?;
+    submission_duplicate::Entity::delete_many()
+        .filter(
+            submission_duplicate::Column::SubmissionId
+                .eq(submission_id)
+                .or(submission_duplicate::Column::OriginalId.eq(submission_id)),
         )
-    })?;
-
-    match CONFIG.base_url.join(&format!("./art/{}", art.id)) {
-        Ok(url) => {
-            if let Err(err) = post_note(
-                &state.http_client,
-                format!(
-                    "**{}**에 새 그림이 등록되었어요!\n> {}@{} - {}\n{}보러가기: {}",
-                    CONFIG.contest_name,
-                    art.author_handle,
-                    art.author_instance,
-                    art.title,
-                    if art.is_nsfw { "**!!!NSFW!!!**\n" } else { "" },
-                    url
-                ),
+        .exec(conn)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to delete from database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to delete from database",
             )
-            .await
-            {
-                tracing::warn!(?err, "failed to post note");
-            }
-        }
-        Err(err) => {
-            tracing::warn!(?err, "failed to join literature URL");
-        }
-    }
+        })?;
 
-    Ok(Json(art::Metadata {
-        id: art.id,
-        title: art.title,
-        description: art.description,
-        is_nsfw: art.is_nsfw,
-        author_handle: art.author_handle,
-        author_instance: art.author_instance,
-    }))
+    Ok(())
 }
@@ -1,55 +1,62 @@
+use std::collections::HashSet;
+
 use axum::{extract, http::StatusCode, routing, Json, Router};
 use sea_orm::{
-    ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter,
-    TransactionTrait,
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseTransaction, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, TransactionTrait,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
 use crate::{
-    config::CONFIG,
-    entity::{art, art_vote, literature, literature_vote},
+    config::Category,
+    entity::{
+        contest::{self, VotingMethod},
+        submission, vote,
+    },
     handler::{api::oauth::User, AppState},
 };
 
-use super::GetOpenedResp;
+use super::{lock_user_rows, Contest, ContestCategory, GetOpenedResp, IdPath};
 
 pub(super) fn create_router() -> Router<AppState> {
     Router::new()
         .route("/opened", routing::get(get_opened))
+        .route("/:category", routing::get(get_ballot).put(put_ballot))
         .route(
-            "/literature/:id",
-            routing::get(get_literature).post(post_literature),
+            "/:category/:id",
+            routing::get(get_vote).post(post_vote).delete(delete_vote),
         )
-        .route("/art/:id", routing::get(get_art).post(post_art))
 }
 
-async fn get_opened() -> Json<GetOpenedResp> {
+async fn get_opened(Contest(contest): Contest) -> Json<GetOpenedResp> {
     let now = OffsetDateTime::now_utc();
-    let opened = now >= CONFIG.voting_open_at && now <= CONFIG.voting_close_at;
+    let opened = now >= contest.voting_open_at && now <= contest.voting_close_at;
     Json(GetOpenedResp {
         opened,
-        open_at: CONFIG.voting_open_at,
-        close_at: CONFIG.voting_close_at,
+        open_at: contest.voting_open_at,
+        close_at: contest.voting_close_at,
     })
 }
 
+/// Highest score of a single vote in score voting.
+const MAX_SCORE: i32 = 5;
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 struct GetVoteResp {
     voted: bool,
     vote_count: u64,
+    score: Option<i32>,
+    rank: Option<i32>,
 }
 
-async fn get_literature(
+async fn get_vote(
     user: User,
+    ContestCategory(contest, category): ContestCategory,
     extract::State(state): extract::State<AppState>,
-    extract::Path(id): extract::Path<i32>,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
 ) -> Result<Json<GetVoteResp>, (StatusCode, &'static str)> {
-    if !CONFIG.literature_enabled {
-        return Err((StatusCode::BAD_REQUEST, "literature not enabled"));
-    }
-
     let tx = state.db.begin().await.map_err(|err| {
         tracing::error!(?err, "failed to begin transaction");
         (
@@ -58,14 +65,14 @@ async fn get_literature(
         )
     })?;
 
-    let voted = literature_vote::Entity::find()
+    let vote = vote::Entity::find()
         .filter(
-            literature_vote::Column::Handle
-                .eq(&user.handle)
-                .and(literature_vote::Column::Instance.eq(&user.instance))
-                .and(literature_vote::Column::LiteratureId.eq(id)),
+            vote::Column::AccountId
+                .eq(&user.account_id)
+                .and(vote::Column::Instance.eq(&user.instance))
+                .and(vote::Column::SubmissionId.eq(id)),
         )
-        .count(&tx)
+        .one(&tx)
         .await
         .map_err(|err| {
             tracing::error!(?err, "failed to query database");
@@ -75,11 +82,13 @@ async fn get_literature(
             )
         })?;
 
-    let vote_count = literature_vote::Entity::find()
+    let vote_count = vote::Entity::find()
         .filter(
-            literature_vote::Column::Handle
-                .eq(&user.handle)
-                .and(literature_vote::Column::Instance.eq(&user.instance)),
+            vote::Column::ContestId
+                .eq(contest.id)
+                .and(vote::Column::Category.eq(&category.slug))
+                .and(vote::Column::AccountId.eq(&user.account_id))
+                .and(vote::Column::Instance.eq(&user.instance)),
         )
         .count(&tx)
         .await
@@ -92,22 +101,46 @@ async fn get_literature(
         })?;
 
     Ok(Json(GetVoteResp {
-        voted: voted > 0,
+        voted: vote.is_some(),
         vote_count,
+        score: vote.as_ref().and_then(|vote| vote.score),
+        rank: vote.as_ref().and_then(|vote| vote.rank),
     }))
 }
 
-async fn post_literature(
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PostVoteReq {
+    score: i32,
+}
+
+/// Approval votes are cast without a body, score votes with a score. Casting a score vote again
+/// changes the score.
+async fn post_vote(
     user: User,
+    ContestCategory(contest, category): ContestCategory,
     extract::State(state): extract::State<AppState>,
-    extract::Path(id): extract::Path<i32>,
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
+    req: Option<Json<PostVoteReq>>,
 ) -> Result<(), (StatusCode, &'static str)> {
-    if !CONFIG.literature_enabled {
-        return Err((StatusCode::BAD_REQUEST, "literature not enabled"));
-    }
+    let score = match contest.voting_method {
+        VotingMethod::Approval => None,
+        VotingMethod::Score => {
+            let Some(Json(PostVoteReq { score })) = req else {
+                return Err((StatusCode::BAD_REQUEST, "score not found"));
+            };
+            if !(1..=MAX_SCORE).contains(&score) {
+                return Err((StatusCode::BAD_REQUEST, "invalid score"));
+            }
+            Some(score)
+        }
+        VotingMethod::Ranked => {
+            return Err((StatusCode::BAD_REQUEST, "ranked voting takes a ballot"));
+        }
+    };
 
     let now = OffsetDateTime::now_utc();
-    if now < CONFIG.voting_open_at || now > CONFIG.voting_close_at {
+    if now < contest.voting_open_at || now > contest.voting_close_at {
         return Err((StatusCode::BAD_REQUEST, "voting not available"));
     }
 
@@ -119,7 +152,13 @@ async fn post_literature(
         )
     })?;
 
-    let literature = literature::Entity::find_by_id(id)
+    // The vote limit and the existing vote are checked before inserting.
+    lock_user_rows(&tx, "vote", &contest, category, &user).await?;
+
+    let submission = submission::Entity::find_by_id(id)
+        .filter(submission::Column::ContestId.eq(contest.id))
+        .filter(submission::Column::Category.eq(&category.slug))
+        .filter(submission::Column::IsHidden.eq(false))
         .count(&tx)
         .await
         .map_err(|err| {
@@ -129,18 +168,18 @@ async fn post_literature(
                 "failed to query database",
             )
         })?;
-    if literature == 0 {
-        return Err((StatusCode::NOT_FOUND, "literature not found"));
+    if submission == 0 {
+        return Err((StatusCode::NOT_FOUND, "submission not found"));
     }
 
-    let existing_vote = literature_vote::Entity::find()
+    let existing_vote = vote::Entity::find()
         .filter(
-            literature_vote::Column::Handle
-                .eq(&user.handle)
-                .and(literature_vote::Column::Instance.eq(&user.instance))
-                .and(literature_vote::Column::LiteratureId.eq(id)),
+            vote::Column::AccountId
+                .eq(&user.account_id)
+                .and(vote::Column::Instance.eq(&user.instance))
+                .and(vote::Column::SubmissionId.eq(id)),
         )
-        .count(&tx)
+        .one(&tx)
         .await
         .map_err(|err| {
             tracing::error!(?err, "failed to query database");
@@ -149,17 +188,52 @@ async fn post_literature(
                 "failed to query database",
             )
         })?;
-    if existing_vote > 0 {
-        return Err((StatusCode::CONFLICT, "already voted"));
+    if let Some(existing_vote) = existing_vote {
+        if score.is_none() {
+            return Err((StatusCode::CONFLICT, "already voted"));
+        }
+
+        let mut vote_activemodel: vote::ActiveModel = existing_vote.into();
+        vote_activemodel.score = ActiveValue::Set(score);
+        vote_activemodel.update(&tx).await.map_err(|err| {
+            tracing::error!(?err, "failed to update database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to update database",
+            )
+        })?;
+    } else {
+        insert_vote(&tx, &user, &contest, category, id, score).await?;
     }
 
-    let existing_vote_count = literature_vote::Entity::find()
+    tx.commit().await.map_err(|err| {
+        tracing::error!(?err, "failed to commit to database");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to commit to database",
+        )
+    })?;
+
+    Ok(())
+}
+
+async fn insert_vote(
+    tx: &DatabaseTransaction,
+    user: &User,
+    contest: &contest::Model,
+    category: &Category,
+    id: i32,
+    score: Option<i32>,
+) -> Result<(), (StatusCode, &'static str)> {
+    let existing_vote_count = vote::Entity::find()
         .filter(
-            literature_vote::Column::Handle
-                .eq(&user.handle)
-                .and(literature_vote::Column::Instance.eq(&user.instance)),
+            vote::Column::ContestId
+                .eq(contest.id)
+                .and(vote::Column::Category.eq(&category.slug))
+                .and(vote::Column::AccountId.eq(&user.account_id))
+                .and(vote::Column::Instance.eq(&user.instance)),
         )
-        .count(&tx)
+        .count(tx)
         .await
         .map_err(|err| {
             tracing::error!(?err, "failed to query database");
@@ -169,80 +243,97 @@ async fn post_literature(
             )
         })?;
 
-    if existing_vote_count >= 5 {
+    if existing_vote_count >= category.vote_limit {
         return Err((StatusCode::CONFLICT, "too many vote"));
     }
 
-    let literature_vote_activemodel = literature_vote::ActiveModel {
+    let vote_activemodel = vote::ActiveModel {
         id: ActiveValue::NotSet,
-        handle: ActiveValue::Set(user.handle),
-        instance: ActiveValue::Set(user.instance),
-        literature_id: ActiveValue::Set(id),
+        handle: ActiveValue::Set(user.handle.clone()),
+        instance: ActiveValue::Set(user.instance.clone()),
+        account_id: ActiveValue::Set(Some(user.account_id.clone())),
+        contest_id: ActiveValue::Set(contest.id),
+        category: ActiveValue::Set(category.slug.clone()),
+        submission_id: ActiveValue::Set(id),
+        score: ActiveValue::Set(score),
+        rank: ActiveValue::NotSet,
     };
 
-    literature_vote_activemodel
-        .insert(&tx)
-        .await
-        .map_err(|err| {
-            tracing::error!(?err, "failed to insert to database");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "failed to insert to database",
-            )
-        })?;
-
-    tx.commit().await.map_err(|err| {
-        tracing::error!(?err, "failed to commit to database");
+    vote_activemodel.insert(tx).await.map_err(|err| {
+        tracing::error!(?err, "failed to insert to database");
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to commit to database",
+            "failed to insert to database",
         )
     })?;
 
     Ok(())
 }
 
-async fn get_art(
+async fn delete_vote(
     user: User,
+    ContestCategory(contest, category): ContestCategory,
     extract::State(state): extract::State<AppState>,
-    extract::Path(id): extract::Path<i32>,
-) -> Result<Json<GetVoteResp>, (StatusCode, &'static str)> {
-    if !CONFIG.art_enabled {
-        return Err((StatusCode::BAD_REQUEST, "art not enabled"));
+    extract::Path(IdPath { id }): extract::Path<IdPath>,
+) -> Result<(), (StatusCode, &'static str)> {
+    if contest.voting_method == VotingMethod::Ranked {
+        return Err((StatusCode::BAD_REQUEST, "ranked voting takes a ballot"));
     }
 
-    let tx = state.db.begin().await.map_err(|err| {
-        tracing::error!(?err, "failed to begin transaction");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to begin transaction",
-        )
-    })?;
+    let now = OffsetDateTime::now_utc();
+    if now < contest.voting_open_at || now > contest.voting_close_at {
+        return Err((StatusCode::BAD_REQUEST, "voting not available"));
+    }
 
-    let voted = art_vote::Entity::find()
+    let res = vote::Entity::delete_many()
         .filter(
-            art_vote::Column::Handle
-                .eq(&user.handle)
-                .and(art_vote::Column::Instance.eq(&user.instance))
-                .and(art_vote::Column::ArtId.eq(id)),
+            vote::Column::ContestId
+                .eq(contest.id)
+                .and(vote::Column::Category.eq(&category.slug))
+                .and(vote::Column::AccountId.eq(&user.account_id))
+                .and(vote::Column::Instance.eq(&user.instance))
+                .and(vote::Column::SubmissionId.eq(id)),
         )
-        .count(&tx)
+        .exec(&*state.db)
         .await
         .map_err(|err| {
-            tracing::error!(?err, "failed to query database");
+            tracing::error!(?err, "failed to delete from database");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "failed to query database",
+                "failed to delete from database",
             )
         })?;
+    if res.rows_affected == 0 {
+        return Err((StatusCode::NOT_FOUND, "vote not found"));
+    }
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BallotEntry {
+    submission_id: i32,
+    score: Option<i32>,
+    rank: Option<i32>,
+}
 
-    let vote_count = art_vote::Entity::find()
+async fn get_ballot(
+    user: User,
+    ContestCategory(contest, category): ContestCategory,
+    extract::State(state): extract::State<AppState>,
+) -> Result<Json<Vec<BallotEntry>>, (StatusCode, &'static str)> {
+    let votes = vote::Entity::find()
         .filter(
-            art_vote::Column::Handle
-                .eq(&user.handle)
-                .and(art_vote::Column::Instance.eq(&user.instance)),
+            vote::Column::ContestId
+                .eq(contest.id)
+                .and(vote::Column::Category.eq(&category.slug))
+                .and(vote::Column::AccountId.eq(&user.account_id))
+                .and(vote::Column::Instance.eq(&user.instance)),
         )
-        .count(&tx)
+        .order_by_asc(vote::Column::Rank)
+        .order_by_asc(vote::Column::Id)
+        .all(&*state.db)
         .await
         .map_err(|err| {
             tracing::error!(?err, "failed to query database");
@@ -252,26 +343,48 @@ async fn get_art(
             )
         })?;
 
-    Ok(Json(GetVoteResp {
-        voted: voted > 0,
-        vote_count,
-    }))
+    Ok(Json(
+        votes
+            .into_iter()
+            .map(|vote| BallotEntry {
+                submission_id: vote.submission_id,
+                score: vote.score,
+                rank: vote.rank,
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PutBallotReq {
+    /// Submission IDs, most preferred first.
+    ranking: Vec<i32>,
 }
 
-async fn post_art(
+/// Replaces the whole ranked ballot of the user. An empty ranking retracts the ballot.
+async fn put_ballot(
     user: User,
+    ContestCategory(contest, category): ContestCategory,
     extract::State(state): extract::State<AppState>,
-    extract::Path(id): extract::Path<i32>,
+    Json(req): Json<PutBallotReq>,
 ) -> Result<(), (StatusCode, &'static str)> {
-    if !CONFIG.art_enabled {
-        return Err((StatusCode::BAD_REQUEST, "art not enabled"));
+    if contest.voting_method != VotingMethod::Ranked {
+        return Err((StatusCode::BAD_REQUEST, "ballot not available"));
     }
 
     let now = OffsetDateTime::now_utc();
-    if now < CONFIG.voting_open_at || now > CONFIG.voting_close_at {
+    if now < contest.voting_open_at || now > contest.voting_close_at {
         return Err((StatusCode::BAD_REQUEST, "voting not available"));
     }
 
+    if req.ranking.len() as u64 > category.vote_limit {
+        return Err((StatusCode::CONFLICT, "too many vote"));
+    }
+    if req.ranking.iter().collect::<HashSet<_>>().len() != req.ranking.len() {
+        return Err((StatusCode::BAD_REQUEST, "duplicated submission"));
+    }
+
     let tx = state.db.begin().await.map_err(|err| {
         tracing::error!(?err, "failed to begin transaction");
         (
@@ -280,7 +393,11 @@ async fn post_art(
         )
     })?;
 
-    let art = art::Entity::find_by_id(id)
+    let submission_count = submission::Entity::find()
+        .filter(submission::Column::Id.is_in(req.ranking.iter().copied()))
+        .filter(submission::Column::ContestId.eq(contest.id))
+        .filter(submission::Column::Category.eq(&category.slug))
+        .filter(submission::Column::IsHidden.eq(false))
         .count(&tx)
         .await
         .map_err(|err| {
@@ -290,65 +407,54 @@ async fn post_art(
                 "failed to query database",
             )
         })?;
-    if art == 0 {
-        return Err((StatusCode::NOT_FOUND, "art not found"));
+    if submission_count != req.ranking.len() as u64 {
+        return Err((StatusCode::NOT_FOUND, "submission not found"));
     }
 
-    let existing_vote = art_vote::Entity::find()
+    lock_user_rows(&tx, "vote", &contest, category, &user).await?;
+    vote::Entity::delete_many()
         .filter(
-            art_vote::Column::Handle
-                .eq(&user.handle)
-                .and(art_vote::Column::Instance.eq(&user.instance))
-                .and(art_vote::Column::ArtId.eq(id)),
+            vote::Column::ContestId
+                .eq(contest.id)
+                .and(vote::Column::Category.eq(&category.slug))
+                .and(vote::Column::AccountId.eq(&user.account_id))
+                .and(vote::Column::Instance.eq(&user.instance)),
         )
-        .count(&tx)
+        .exec(&tx)
         .await
         .map_err(|err| {
-            tracing::error!(?err, "failed to query database");
+            tracing::error!(?err, "failed to delete from database");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "failed to query database",
+                "failed to delete from database",
             )
         })?;
-    if existing_vote > 0 {
-        return Err((StatusCode::CONFLICT, "already voted"));
-    }
 
-    let existing_vote_count = art_vote::Entity::find()
-        .filter(
-            art_vote::Column::Handle
-                .eq(&user.handle)
-                .and(art_vote::Column::Instance.eq(&user.instance)),
-        )
-        .count(&tx)
+    if !req.ranking.is_empty() {
+        vote::Entity::insert_many(req.ranking.into_iter().zip(1..).map(|(id, rank)| {
+            vote::ActiveModel {
+                id: ActiveValue::NotSet,
+                handle: ActiveValue::Set(user.handle.clone()),
+                instance: ActiveValue::Set(user.instance.clone()),
+                account_id: ActiveValue::Set(Some(user.account_id.clone())),
+                contest_id: ActiveValue::Set(contest.id),
+                category: ActiveValue::Set(category.slug.clone()),
+                submission_id: ActiveValue::Set(id),
+                score: ActiveValue::Set(None),
+                rank: ActiveValue::Set(Some(rank)),
+            }
+        }))
+        .exec(&tx)
         .await
         .map_err(|err| {
-            tracing::error!(?err, "failed to query database");
+            tracing::error!(?err, "failed to insert to database");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "failed to query database",
+                "failed to insert to database",
             )
         })?;
-
-    if existing_vote_count >= 5 {
-        return Err((StatusCode::CONFLICT, "too many vote"));
     }
 
-    let art_vote_activemodel = art_vote::ActiveModel {
-        id: ActiveValue::NotSet,
-        handle: ActiveValue::Set(user.handle),
-        instance: ActiveValue::Set(user.instance),
-        art_id: ActiveValue::Set(id),
-    };
-
-    art_vote_activemodel.insert(&tx).await.map_err(|err| {
-        tracing::error!(?err, "failed to insert to database");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to insert to database",
-        )
-    })?;
-
     tx.commit().await.map_err(|err| {
         tracing::error!(?err, "failed to commit to database");
         (
@@ -1,36 +1,57 @@
 use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+
 use axum::{
     extract::{self, rejection::TypedHeaderRejectionReason, FromRequestParts},
     headers,
-    http::{header, request::Parts, HeaderMap, StatusCode},
-    response::Redirect,
+    http::{header, request::Parts, HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{Redirect, Response},
     routing, Json, RequestPartsExt, Router, TypedHeader,
 };
-use jsonwebtoken::Validation;
 use sea_orm::{
-    ActiveModelTrait, ActiveValue, ColumnTrait, EntityTrait, QueryFilter, TransactionTrait,
+    sea_query::{Expr, OnConflict},
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, EntityTrait, QueryFilter,
+    TransactionTrait,
 };
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use time::{Duration, OffsetDateTime};
 use url::Url;
 
-use crate::{config::CONFIG, entity::instance, handler::AppState, utils::detect_instance};
+use crate::{
+    config::CONFIG,
+    entity::{account, instance, jury_score, session, submission, vote},
+    handler::AppState,
+    utils::{detect_instance, resolve_account_domain, resolve_api_host},
+};
+
+mod mastodon;
+mod misskey;
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Time given to log in on the instance and come back.
+const LOGIN_SESSION_LIFETIME: Duration = Duration::minutes(10);
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct User {
     pub handle: String,
     pub instance: String,
+    /// ID of the account on its instance, which identifies the user even after a rename. The
+    /// handle is only shown.
+    pub account_id: String,
+    /// When the session expires, as a Unix timestamp.
     exp: i64,
 }
 
 #[async_trait]
-impl<S> FromRequestParts<S> for User
-where
-    S: Send + Sync,
-{
+impl FromRequestParts<AppState> for User {
     type Rejection = (StatusCode, &'static str);
 
-    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
         let cookies = parts
             .extract::<TypedHeader<headers::Cookie>>()
             .await
@@ -47,21 +68,114 @@ where
             .get("SESSION")
             .ok_or((StatusCode::UNAUTHORIZED, "user not authorized"))?;
 
-        let mut jwt_validation = Validation::default();
-        jwt_validation.validate_exp = true;
-        let user_data =
-            jsonwebtoken::decode::<User>(session_cookie, &CONFIG.jwt_secret.1, &jwt_validation)
-                .map_err(|_| (StatusCode::UNAUTHORIZED, "user not authorized"))?;
-        let user = user_data.claims;
+        let now = OffsetDateTime::now_utc();
+        let mut session = session::Entity::find()
+            .filter(session::Column::TokenHash.eq(hash_session_token(session_cookie)))
+            .filter(session::Column::ExpiresAt.gt(now))
+            .one(&*state.db)
+            .await
+            .map_err(|err| {
+                tracing::error!(?err, "failed to query database");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed to query database",
+                )
+            })?
+            .ok_or((StatusCode::UNAUTHORIZED, "user not authorized"))?;
+
+        // Sessions in use are kept alive once they are past the half of their lifetime, so active
+        // users are not logged out while a stolen cookie still expires soon after it stops being
+        // used.
+        if session.expires_at - now < session_lifetime() / 2 {
+            let expires_at = now + session_lifetime();
+            session::Entity::update_many()
+                .col_expr(session::Column::ExpiresAt, Expr::value(expires_at))
+                .filter(session::Column::Id.eq(session.id))
+                .exec(&*state.db)
+                .await
+                .map_err(|err| {
+                    tracing::error!(?err, "failed to update database");
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        "failed to update database",
+                    )
+                })?;
+            session.expires_at = expires_at;
+
+            if let Some(RefreshedCookie(refreshed_cookie)) = parts.extensions.get() {
+                *refreshed_cookie.lock().unwrap() =
+                    Some(cookie("SESSION", session_cookie, session_lifetime())?);
+            }
+        }
 
-        Ok(user)
+        Ok(User {
+            handle: session.handle,
+            instance: session.instance,
+            account_id: session.account_id,
+            exp: session.expires_at.unix_timestamp(),
+        })
     }
 }
 
+/// Slot for the `User` extractor to hand a refreshed `SESSION` cookie to
+/// `refresh_cookie_middleware`, as extractors cannot set response headers.
+#[derive(Clone, Default)]
+struct RefreshedCookie(Arc<Mutex<Option<HeaderValue>>>);
+
+/// Sets the `SESSION` cookie again if the `User` extractor extended the session.
+pub(super) async fn refresh_cookie_middleware<B>(mut req: Request<B>, next: Next<B>) -> Response {
+    let refreshed_cookie = RefreshedCookie::default();
+    req.extensions_mut().insert(refreshed_cookie.clone());
+
+    let mut resp = next.run(req).await;
+    if let Some(cookie) = refreshed_cookie.0.lock().unwrap().take() {
+        resp.headers_mut().append(header::SET_COOKIE, cookie);
+    }
+    resp
+}
+
+fn session_lifetime() -> Duration {
+    Duration::seconds(CONFIG.session_lifetime_secs)
+}
+
+/// `Set-Cookie` value of a cookie hidden from scripts, and only sent over HTTPS if the site is
+/// served over HTTPS. An empty value with no lifetime clears the cookie.
+fn cookie(
+    name: &str,
+    value: &str,
+    max_age: Duration,
+) -> Result<HeaderValue, (StatusCode, &'static str)> {
+    let secure = if CONFIG.base_url.scheme() == "https" {
+        "; Secure"
+    } else {
+        ""
+    };
+    format!(
+        "{}={}; Max-Age={}; SameSite=Lax; Path=/; HttpOnly{}",
+        name,
+        value,
+        max_age.whole_seconds(),
+        secure
+    )
+    .parse()
+    .map_err(|err| {
+        tracing::error!(?err, "failed to generate session cookie value");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to generate session cookie value",
+        )
+    })
+}
+
+fn hash_session_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
 pub(super) fn create_router() -> Router<AppState> {
     Router::new()
         .route("/authorize", routing::post(post_authorize))
         .route("/redirect", routing::get(get_redirect))
+        .route("/logout", routing::post(post_logout))
 }
 
 #[derive(Deserialize)]
@@ -74,56 +188,66 @@ struct PostAuthorizeResp {
     url: String,
 }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct MisskeyAppCreateReq {
-    name: String,
-    description: String,
-    permission: Vec<String>,
-    callback_url: Url,
+/// API family of a fediverse software, which decides how its users log in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ApiFamily {
+    /// Misskey and its forks, through authentication sessions.
+    Misskey,
+    /// Mastodon and software implementing its client API, through OAuth.
+    Mastodon,
 }
 
-#[derive(Deserialize)]
-struct MisskeyAppCreateResp {
-    id: String,
-    secret: String,
-}
-
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct MisskeySessionGenerateReq {
-    app_secret: String,
+/// Supported software by their nodeinfo `software.name`. Forks report their own names, so each
+/// is listed.
+const SOFTWARE_API_FAMILIES: &[(&str, ApiFamily)] = &[
+    ("misskey", ApiFamily::Misskey),
+    ("cherrypick", ApiFamily::Misskey),
+    ("castella", ApiFamily::Misskey),
+    ("sharkey", ApiFamily::Misskey),
+    ("firefish", ApiFamily::Misskey),
+    ("calckey", ApiFamily::Misskey),
+    ("iceshrimp", ApiFamily::Misskey),
+    ("foundkey", ApiFamily::Misskey),
+    ("mastodon", ApiFamily::Mastodon),
+    ("hometown", ApiFamily::Mastodon),
+    ("fedibird", ApiFamily::Mastodon),
+    ("pleroma", ApiFamily::Mastodon),
+    ("akkoma", ApiFamily::Mastodon),
+    ("gotosocial", ApiFamily::Mastodon),
+];
+
+fn api_family(software: &str) -> Option<ApiFamily> {
+    SOFTWARE_API_FAMILIES
+        .iter()
+        .find(|(name, _)| software.eq_ignore_ascii_case(name))
+        .map(|(_, api_family)| *api_family)
 }
 
-#[derive(Deserialize)]
-struct MisskeySessionGenerateResp {
-    url: String,
-}
-
-#[derive(Serialize)]
-struct MastodonPostAppReq {
-    client_name: String,
-    redirect_uris: Url,
-    scopes: String,
-    website: Url,
-}
-
-#[derive(Deserialize)]
-struct MastodonPostAppResp {
-    client_id: String,
-    client_secret: String,
+fn redirect_url() -> Result<Url, (StatusCode, &'static str)> {
+    CONFIG.base_url.join("/api/oauth/redirect").map_err(|err| {
+        tracing::error!(?err, "failed to generate redirect URL");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to generate redirect URL",
+        )
+    })
 }
 
 async fn post_authorize(
     extract::State(state): extract::State<AppState>,
     Json(req): Json<PostAuthorizeReq>,
 ) -> Result<(HeaderMap, Json<PostAuthorizeResp>), (StatusCode, &'static str)> {
-    let (_, instance_name) = req.instance.rsplit_once('@').unwrap_or(("", &req.instance));
+    let instance_name = resolve_api_host(&state.http_client, &req.instance)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to parse instance URL");
+            (StatusCode::BAD_REQUEST, "failed to parse instance URL")
+        })?;
     let instance_url = Url::parse(&format!("https://{}", instance_name)).map_err(|err| {
         tracing::error!(?err, "failed to parse instance URL");
         (StatusCode::BAD_REQUEST, "failed to parse instance URL")
     })?;
-    let instance_type = detect_instance(&state.http_client, instance_url)
+    let software = detect_instance(&state.http_client, instance_url)
         .await
         .map_err(|err| {
             tracing::error!(?err, "failed to detect instance type");
@@ -133,15 +257,13 @@ async fn post_authorize(
             )
         })?
         .ok_or((StatusCode::BAD_REQUEST, "failed to detect instance type"))?;
-
-    let redirect_url = CONFIG.base_url.join("/api/oauth/redirect").map_err(|err| {
-        tracing::error!(?err, "failed to generate redirect URL");
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "failed to generate redirect URL",
-        )
+    let api_family = api_family(&software).ok_or_else(|| {
+        tracing::info!(software, instance = instance_name, "unsupported software");
+        (StatusCode::BAD_REQUEST, "unsupported instance software")
     })?;
 
+    let redirect_url = redirect_url()?;
+
     let tx = state.db.begin().await.map_err(|err| {
         tracing::error!(?err, "failed to begin transaction");
         (
@@ -150,7 +272,7 @@ async fn post_authorize(
         )
     })?;
 
-    let instance = instance::Entity::find_by_id(instance_name)
+    let instance = instance::Entity::find_by_id(&instance_name)
         .one(&tx)
         .await
         .map_err(|err| {
@@ -160,205 +282,55 @@ async fn post_authorize(
                 "failed to query database",
             )
         })?;
-    if instance_type == "misskey" || instance_type == "cherrypick" || instance_type == "castella" {
-        let instance = if let Some(instance) = instance {
-            instance
-        } else {
-            let resp = state
-                .http_client
-                .post(format!("https://{}/api/app/create", instance_name))
-                .json(&MisskeyAppCreateReq {
-                    name: format!("{}/{}", env!("CARGO_PKG_NAME"), CONFIG.contest_name),
-                    description: "contest controller".to_string(),
-                    permission: Vec::new(),
-                    callback_url: redirect_url,
-                })
-                .send()
-                .await
-                .map_err(|err| {
-                    tracing::error!(?err, "failed to request to Misskey instance");
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "failed to request to Misskey instance",
-                    )
-                })?
-                .json::<MisskeyAppCreateResp>()
-                .await
-                .map_err(|err| {
-                    tracing::error!(?err, "failed to parse Misskey response");
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "failed to parse Misskey response",
-                    )
-                })?;
-
-            let instance_activemodel = instance::ActiveModel {
-                hostname: ActiveValue::Set(instance_name.to_string()),
-                client_id: ActiveValue::Set(resp.id),
-                client_secret: ActiveValue::Set(resp.secret),
-            };
+    let instance = if let Some(instance) = instance {
+        instance
+    } else {
+        let (client_id, client_secret) = match api_family {
+            ApiFamily::Misskey => {
+                misskey::create_app(&state.http_client, &instance_name, redirect_url.clone())
+                    .await?
+            }
+            ApiFamily::Mastodon => {
+                mastodon::create_app(&state.http_client, &instance_name, redirect_url.clone())
+                    .await?
+            }
+        };
 
-            instance_activemodel.insert(&tx).await.map_err(|err| {
-                tracing::error!(?err, "failed to insert to database");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to insert to database",
-                )
-            })?
+        let instance_activemodel = instance::ActiveModel {
+            hostname: ActiveValue::Set(instance_name.clone()),
+            client_id: ActiveValue::Set(client_id),
+            client_secret: ActiveValue::Set(client_secret),
         };
 
-        tx.commit().await.map_err(|err| {
-            tracing::error!(?err, "failed to commit to database");
+        instance_activemodel.insert(&tx).await.map_err(|err| {
+            tracing::error!(?err, "failed to insert to database");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "failed to commit to database",
-            )
-        })?;
-
-        let resp = state
-            .http_client
-            .post(format!(
-                "https://{}/api/auth/session/generate",
-                instance_name
-            ))
-            .json(&MisskeySessionGenerateReq {
-                app_secret: instance.client_secret,
-            })
-            .send()
-            .await
-            .map_err(|err| {
-                tracing::error!(?err, "failed to request to Misskey instance");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to request to Misskey instance",
-                )
-            })?
-            .json::<MisskeySessionGenerateResp>()
-            .await
-            .map_err(|err| {
-                tracing::error!(?err, "failed to parse Misskey response");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to parse Misskey response",
-                )
-            })?;
-
-        let mut header_map = HeaderMap::new();
-        header_map.insert(
-            header::SET_COOKIE,
-            format!(
-                "LOGIN_SESSION=misskey_{}; SameSite=Lax; Path=/",
-                instance.client_id
+                "failed to insert to database",
             )
-            .parse()
-            .map_err(|err| {
-                tracing::error!(?err, "failed to generate session cookie value");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to generate session cookie value",
-                )
-            })?,
-        );
-
-        Ok((header_map, Json(PostAuthorizeResp { url: resp.url })))
-    } else {
-        // Mastodon
-        let instance = if let Some(instance) = instance {
-            instance
-        } else {
-            let resp = state
-                .http_client
-                .post(format!("https://{}/api/v1/apps", instance_name))
-                .json(&MastodonPostAppReq {
-                    client_name: format!("{}/{}", env!("CARGO_PKG_NAME"), CONFIG.contest_name),
-                    redirect_uris: redirect_url.clone(),
-                    scopes: String::new(),
-                    website: CONFIG.base_url.clone(),
-                })
-                .send()
-                .await
-                .map_err(|err| {
-                    tracing::error!(?err, "failed to request to Mastodon instance");
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "failed to request to Mastodon instance",
-                    )
-                })?
-                .json::<MastodonPostAppResp>()
-                .await
-                .map_err(|err| {
-                    tracing::error!(?err, "failed to parse Mastodon response");
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "failed to parse Mastodon response",
-                    )
-                })?;
-
-            let instance_activemodel = instance::ActiveModel {
-                hostname: ActiveValue::Set(instance_name.to_string()),
-                client_id: ActiveValue::Set(resp.client_id),
-                client_secret: ActiveValue::Set(resp.client_secret),
-            };
+        })?
+    };
 
-            instance_activemodel.insert(&tx).await.map_err(|err| {
-                tracing::error!(?err, "failed to insert to database");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to insert to database",
-                )
-            })?
-        };
+    tx.commit().await.map_err(|err| {
+        tracing::error!(?err, "failed to commit to database");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to commit to database",
+        )
+    })?;
 
-        tx.commit().await.map_err(|err| {
-            tracing::error!(?err, "failed to commit to database");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "failed to commit to database",
-            )
-        })?;
+    let (login_session, url) = match api_family {
+        ApiFamily::Misskey => misskey::start_login(&state.http_client, instance).await?,
+        ApiFamily::Mastodon => mastodon::start_login(instance, &redirect_url)?,
+    };
 
-        let login_state = format!(
-            "{}_{}",
-            random_string::generate(64, "abcdefghijklmnopqrstuvwxyz0123456789"),
-            instance.client_id
-        );
+    let mut header_map = HeaderMap::new();
+    header_map.insert(
+        header::SET_COOKIE,
+        cookie("LOGIN_SESSION", &login_session, LOGIN_SESSION_LIFETIME)?,
+    );
 
-        let mut url =
-            Url::parse(&format!("https://{}/oauth/authorize", instance_name)).map_err(|err| {
-                tracing::error!(?err, "failed to parse redirect URL");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to parse redirect URL",
-                )
-            })?;
-        url.query_pairs_mut()
-            .append_pair("client_id", &instance.client_id)
-            .append_pair("scope", "")
-            .append_pair("redirect_uri", redirect_url.as_str())
-            .append_pair("response_type", "code")
-            .append_pair("state", &login_state);
-
-        let mut header_map = HeaderMap::new();
-        header_map.insert(
-            header::SET_COOKIE,
-            format!("LOGIN_SESSION={}; SameSite=Lax; Path=/", login_state)
-                .parse()
-                .map_err(|err| {
-                    tracing::error!(?err, "failed to generate session cookie value");
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "failed to generate session cookie value",
-                    )
-                })?,
-        );
-
-        Ok((
-            header_map,
-            Json(PostAuthorizeResp {
-                url: url.to_string(),
-            }),
-        ))
-    }
+    Ok((header_map, Json(PostAuthorizeResp { url })))
 }
 
 #[derive(Deserialize)]
@@ -368,44 +340,6 @@ enum GetRedirectQuery {
     Mastodon { state: String, code: String },
 }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-struct MisskeyUserkeyReq {
-    app_secret: String,
-    token: String,
-}
-
-#[derive(Deserialize)]
-struct MisskeyUser {
-    username: String,
-}
-
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct MisskeyUserkeyResp {
-    user: MisskeyUser,
-}
-
-#[derive(Serialize)]
-struct MastodonOauthTokenReq {
-    grant_type: String,
-    redirect_uri: Url,
-    client_id: String,
-    client_secret: String,
-    code: String,
-    state: String,
-}
-
-#[derive(Deserialize)]
-struct MastodonOauthTokenResp {
-    access_token: String,
-}
-
-#[derive(Deserialize)]
-struct MastodonVerifyCredentialsResp {
-    username: String,
-}
-
 async fn get_redirect(
     extract::Query(query): extract::Query<GetRedirectQuery>,
     TypedHeader(cookies): TypedHeader<headers::Cookie>,
@@ -415,190 +349,271 @@ async fn get_redirect(
         .get("LOGIN_SESSION")
         .ok_or((StatusCode::BAD_REQUEST, "session not found"))?;
 
-    let mut user = if let Some(client_id) = session.strip_prefix("misskey_") {
-        let token = if let GetRedirectQuery::Misskey { token } = query {
-            token
+    let (api_family, client_id) =
+        if let Some(client_id) = session.strip_prefix(misskey::LOGIN_SESSION_PREFIX) {
+            (ApiFamily::Misskey, client_id)
+        } else if let Some((_, client_id)) = session.split_once('_') {
+            (ApiFamily::Mastodon, client_id)
         } else {
-            return Err((StatusCode::BAD_REQUEST, "token not found"));
+            return Err((StatusCode::BAD_REQUEST, "invalid state"));
         };
 
-        let instance = instance::Entity::find()
-            .filter(instance::Column::ClientId.eq(client_id))
-            .one(&*state.db)
-            .await
-            .map_err(|err| {
-                tracing::error!(?err, "failed to query database");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to query database",
-                )
-            })?
-            .ok_or((StatusCode::NOT_FOUND, "instance not found"))?;
-
-        let resp = state
-            .http_client
-            .post(format!(
-                "https://{}/api/auth/session/userkey",
-                instance.hostname
-            ))
-            .json(&MisskeyUserkeyReq {
-                app_secret: instance.client_secret,
-                token,
-            })
-            .send()
-            .await
-            .map_err(|err| {
-                tracing::error!(?err, "failed to request to Misskey instance");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to request to Misskey instance",
-                )
-            })?
-            .json::<MisskeyUserkeyResp>()
-            .await
-            .map_err(|err| {
-                tracing::error!(?err, "failed to parse Misskey response");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to parse Misskey response",
-                )
-            })?;
+    let instance = instance::Entity::find()
+        .filter(instance::Column::ClientId.eq(client_id))
+        .one(&*state.db)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to query database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to query database",
+            )
+        })?
+        .ok_or((StatusCode::NOT_FOUND, "instance not found"))?;
 
-        User {
-            handle: resp.user.username,
-            instance: instance.hostname,
-            exp: 0,
+    let (account_id, handle) = match (api_family, query) {
+        (ApiFamily::Misskey, GetRedirectQuery::Misskey { token }) => {
+            misskey::finish_login(&state.http_client, &instance, token).await?
         }
-    } else {
-        // Mastodon
-        let (query_state, code) = if let GetRedirectQuery::Mastodon { state, code } = query {
-            (state, code)
-        } else {
-            return Err((StatusCode::BAD_REQUEST, "state not found"));
-        };
-
-        if query_state != session {
-            return Err((StatusCode::BAD_REQUEST, "invalid state"));
+        (ApiFamily::Misskey, _) => return Err((StatusCode::BAD_REQUEST, "token not found")),
+        (
+            ApiFamily::Mastodon,
+            GetRedirectQuery::Mastodon {
+                state: query_state,
+                code,
+            },
+        ) => {
+            if query_state != session {
+                return Err((StatusCode::BAD_REQUEST, "invalid state"));
+            }
+            mastodon::finish_login(
+                &state.http_client,
+                &instance,
+                redirect_url()?,
+                query_state,
+                code,
+            )
+            .await?
         }
+        (ApiFamily::Mastodon, _) => return Err((StatusCode::BAD_REQUEST, "state not found")),
+    };
+    // Accounts are known by their canonical handle, whose domain may not be the API host.
+    let instance = resolve_account_domain(&state.http_client, &handle, &instance.hostname)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to resolve account domain");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to resolve account domain",
+            )
+        })?;
 
-        let client_id = if let Some((_, client_id)) = session.split_once('_') {
-            client_id
-        } else {
-            return Err((StatusCode::BAD_REQUEST, "invalid state"));
-        };
+    let session_token = hex::encode(rand::random::<[u8; 32]>());
+    let now = OffsetDateTime::now_utc();
 
-        let instance = instance::Entity::find()
-            .filter(instance::Column::ClientId.eq(client_id))
-            .one(&*state.db)
-            .await
-            .map_err(|err| {
-                tracing::error!(?err, "failed to query database");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to query database",
-                )
-            })?
-            .ok_or((StatusCode::NOT_FOUND, "instance not found"))?;
-
-        let resp = state
-            .http_client
-            .post(format!("https://{}/oauth/token", instance.hostname))
-            .json(&MastodonOauthTokenReq {
-                grant_type: "authorization_code".to_string(),
-                redirect_uri: CONFIG.base_url.join("/api/oauth/redirect").map_err(|err| {
-                    tracing::error!(?err, "failed to generate redirect URL");
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "failed to generate redirect URL",
-                    )
-                })?,
-                client_id: instance.client_id,
-                client_secret: instance.client_secret,
-                code,
-                state: query_state,
-            })
-            .send()
-            .await
-            .map_err(|err| {
-                tracing::error!(?err, "failed to request to Mastodon instance");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to request to Mastodon instance",
-                )
-            })?
-            .json::<MastodonOauthTokenResp>()
-            .await
-            .map_err(|err| {
-                tracing::error!(?err, "failed to parse Mastodon response");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to parse Mastodon response",
-                )
-            })?;
+    let tx = state.db.begin().await.map_err(|err| {
+        tracing::error!(?err, "failed to begin transaction");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to begin transaction",
+        )
+    })?;
 
-        let resp = state
-            .http_client
-            .get(format!(
-                "https://{}/api/v1/accounts/verify_credentials",
-                instance.hostname
-            ))
-            .header(
-                header::AUTHORIZATION,
-                format!("Bearer {}", resp.access_token),
+    // Expired sessions are swept on login instead of by a job.
+    session::Entity::delete_many()
+        .filter(session::Column::ExpiresAt.lte(now))
+        .exec(&tx)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to delete from database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to delete from database",
             )
-            .send()
-            .await
-            .map_err(|err| {
-                tracing::error!(?err, "failed to request to Mastodon instance");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to request to Mastodon instance",
-                )
-            })?
-            .json::<MastodonVerifyCredentialsResp>()
+        })?;
+
+    // Rows recorded before account IDs are claimed by the account now holding the handle on its
+    // first login only, so an account does not take over the rows of another one that had its
+    // handle before a rename once both are known.
+    let account_activemodel = account::ActiveModel {
+        id: ActiveValue::NotSet,
+        account_id: ActiveValue::Set(account_id.clone()),
+        instance: ActiveValue::Set(instance.clone()),
+        handle: ActiveValue::Set(handle.clone()),
+        created_at: ActiveValue::Set(now),
+    };
+    let is_new_account = account::Entity::insert(account_activemodel)
+        .on_conflict(
+            OnConflict::columns([account::Column::AccountId, account::Column::Instance])
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(&tx)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to insert to database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to insert to database",
+            )
+        })?
+        > 0;
+    if !is_new_account {
+        account::Entity::update_many()
+            .col_expr(account::Column::Handle, Expr::value(handle.clone()))
+            .filter(account::Column::AccountId.eq(&account_id))
+            .filter(account::Column::Instance.eq(&instance))
+            .exec(&tx)
             .await
             .map_err(|err| {
-                tracing::error!(?err, "failed to parse Mastodon response");
+                tracing::error!(?err, "failed to update database");
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    "failed to parse Mastodon response",
+                    "failed to update database",
                 )
             })?;
+    }
 
-        User {
-            handle: resp.username,
-            instance: instance.hostname,
-            exp: 0,
-        }
-    };
-
-    let now = OffsetDateTime::now_utc();
-    let exp = (now + Duration::days(1)).unix_timestamp();
-
-    user.exp = exp;
-
-    let session_token = jsonwebtoken::encode(&Default::default(), &user, &CONFIG.jwt_secret.0)
+    // The handle shown on the rows of the account follows renames.
+    submission::Entity::update_many()
+        .col_expr(
+            submission::Column::AuthorAccountId,
+            Expr::value(account_id.clone()),
+        )
+        .col_expr(
+            submission::Column::AuthorHandle,
+            Expr::value(handle.clone()),
+        )
+        .filter(submission::Column::AuthorInstance.eq(&instance))
+        .filter(
+            Condition::any()
+                .add(submission::Column::AuthorAccountId.eq(&account_id))
+                .add_option(is_new_account.then(|| {
+                    Condition::all()
+                        .add(submission::Column::AuthorAccountId.is_null())
+                        .add(submission::Column::AuthorHandle.eq(&handle))
+                })),
+        )
+        .exec(&tx)
+        .await
         .map_err(|err| {
-            tracing::error!(?err, "failed to generate JWT token");
+            tracing::error!(?err, "failed to update database");
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "failed to generate JWT token",
+                "failed to update database",
+            )
+        })?;
+    vote::Entity::update_many()
+        .col_expr(vote::Column::AccountId, Expr::value(account_id.clone()))
+        .col_expr(vote::Column::Handle, Expr::value(handle.clone()))
+        .filter(vote::Column::Instance.eq(&instance))
+        .filter(
+            Condition::any()
+                .add(vote::Column::AccountId.eq(&account_id))
+                .add_option(is_new_account.then(|| {
+                    Condition::all()
+                        .add(vote::Column::AccountId.is_null())
+                        .add(vote::Column::Handle.eq(&handle))
+                })),
+        )
+        .exec(&tx)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to update database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to update database",
+            )
+        })?;
+    jury_score::Entity::update_many()
+        .col_expr(
+            jury_score::Column::AccountId,
+            Expr::value(account_id.clone()),
+        )
+        .col_expr(jury_score::Column::Handle, Expr::value(handle.clone()))
+        .filter(jury_score::Column::Instance.eq(&instance))
+        .filter(
+            Condition::any()
+                .add(jury_score::Column::AccountId.eq(&account_id))
+                .add_option(is_new_account.then(|| {
+                    Condition::all()
+                        .add(jury_score::Column::AccountId.is_null())
+                        .add(jury_score::Column::Handle.eq(&handle))
+                })),
+        )
+        .exec(&tx)
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to update database");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to update database",
             )
         })?;
 
-    let cookie = format!("SESSION={}; SameSite=Lax; Path=/", session_token);
+    let session_activemodel = session::ActiveModel {
+        id: ActiveValue::NotSet,
+        token_hash: ActiveValue::Set(hash_session_token(&session_token)),
+        handle: ActiveValue::Set(handle),
+        instance: ActiveValue::Set(instance),
+        created_at: ActiveValue::Set(now),
+        expires_at: ActiveValue::Set(now + session_lifetime()),
+        account_id: ActiveValue::Set(account_id),
+    };
+    session_activemodel.insert(&tx).await.map_err(|err| {
+        tracing::error!(?err, "failed to insert to database");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to insert to database",
+        )
+    })?;
+
+    tx.commit().await.map_err(|err| {
+        tracing::error!(?err, "failed to commit to database");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to commit to database",
+        )
+    })?;
 
     let mut header_map = HeaderMap::new();
-    header_map.insert(
+    header_map.append(
         header::SET_COOKIE,
-        cookie.parse().map_err(|err| {
-            tracing::error!(?err, "failed to generate session cookie value");
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "failed to generate session cookie value",
-            )
-        })?,
+        cookie("SESSION", &session_token, session_lifetime())?,
+    );
+    // The login is over, so its state is not needed anymore.
+    header_map.append(
+        header::SET_COOKIE,
+        cookie("LOGIN_SESSION", "", Duration::ZERO)?,
     );
 
     Ok((header_map, Redirect::to("/")))
 }
+
+/// Ends the session of the cookie, if any, and clears the cookie.
+async fn post_logout(
+    cookies: Option<TypedHeader<headers::Cookie>>,
+    extract::State(state): extract::State<AppState>,
+) -> Result<HeaderMap, (StatusCode, &'static str)> {
+    if let Some(session_token) = cookies
+        .as_ref()
+        .and_then(|TypedHeader(cookies)| cookies.get("SESSION"))
+    {
+        session::Entity::delete_many()
+            .filter(session::Column::TokenHash.eq(hash_session_token(session_token)))
+            .exec(&*state.db)
+            .await
+            .map_err(|err| {
+                tracing::error!(?err, "failed to delete from database");
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "failed to delete from database",
+                )
+            })?;
+    }
+
+    let mut header_map = HeaderMap::new();
+    header_map.insert(header::SET_COOKIE, cookie("SESSION", "", Duration::ZERO)?);
+
+    Ok(header_map)
+}
@@ -0,0 +1,179 @@
+use axum::http::{header, StatusCode};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{config::CONFIG, entity::instance};
+
+/// Scope of the access token, only used to look up the account. Mastodon grants it when none is
+/// asked for, but Pleroma, Akkoma and GoToSocial need it given.
+const SCOPES: &str = "read";
+
+#[derive(Serialize)]
+struct PostAppReq {
+    client_name: String,
+    redirect_uris: Url,
+    scopes: String,
+    website: Url,
+}
+
+#[derive(Deserialize)]
+struct PostAppResp {
+    client_id: String,
+    client_secret: String,
+}
+
+#[derive(Serialize)]
+struct OauthTokenReq {
+    grant_type: String,
+    redirect_uri: Url,
+    client_id: String,
+    client_secret: String,
+    code: String,
+    state: String,
+}
+
+#[derive(Deserialize)]
+struct OauthTokenResp {
+    access_token: String,
+}
+
+#[derive(Deserialize)]
+struct VerifyCredentialsResp {
+    id: String,
+    username: String,
+}
+
+/// Registers the site as an OAuth application, and returns its client ID and secret.
+pub(super) async fn create_app(
+    http_client: &reqwest::Client,
+    hostname: &str,
+    redirect_url: Url,
+) -> Result<(String, String), (StatusCode, &'static str)> {
+    let resp = http_client
+        .post(format!("https://{}/api/v1/apps", hostname))
+        .json(&PostAppReq {
+            client_name: CONFIG.site_name.clone(),
+            redirect_uris: redirect_url,
+            scopes: SCOPES.to_string(),
+            website: CONFIG.base_url.clone(),
+        })
+        .send()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to request to Mastodon instance");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to request to Mastodon instance",
+            )
+        })?
+        .json::<PostAppResp>()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to parse Mastodon response");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to parse Mastodon response",
+            )
+        })?;
+
+    Ok((resp.client_id, resp.client_secret))
+}
+
+/// Returns the `LOGIN_SESSION` cookie value, which is also the OAuth state, and the URL the user
+/// authorizes the application at.
+pub(super) fn start_login(
+    instance: instance::Model,
+    redirect_url: &Url,
+) -> Result<(String, String), (StatusCode, &'static str)> {
+    let login_state = format!(
+        "{}_{}",
+        random_string::generate(64, "abcdefghijklmnopqrstuvwxyz0123456789"),
+        instance.client_id
+    );
+
+    let mut url =
+        Url::parse(&format!("https://{}/oauth/authorize", instance.hostname)).map_err(|err| {
+            tracing::error!(?err, "failed to parse redirect URL");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to parse redirect URL",
+            )
+        })?;
+    url.query_pairs_mut()
+        .append_pair("client_id", &instance.client_id)
+        .append_pair("scope", SCOPES)
+        .append_pair("redirect_uri", redirect_url.as_str())
+        .append_pair("response_type", "code")
+        .append_pair("state", &login_state);
+
+    Ok((login_state, url.to_string()))
+}
+
+/// Exchanges the authorization code for an access token, and returns the ID and username of
+/// the account which logged in.
+pub(super) async fn finish_login(
+    http_client: &reqwest::Client,
+    instance: &instance::Model,
+    redirect_url: Url,
+    login_state: String,
+    code: String,
+) -> Result<(String, String), (StatusCode, &'static str)> {
+    let resp = http_client
+        .post(format!("https://{}/oauth/token", instance.hostname))
+        .json(&OauthTokenReq {
+            grant_type: "authorization_code".to_string(),
+            redirect_uri: redirect_url,
+            client_id: instance.client_id.clone(),
+            client_secret: instance.client_secret.clone(),
+            code,
+            state: login_state,
+        })
+        .send()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to request to Mastodon instance");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to request to Mastodon instance",
+            )
+        })?
+        .json::<OauthTokenResp>()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to parse Mastodon response");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to parse Mastodon response",
+            )
+        })?;
+
+    let resp = http_client
+        .get(format!(
+            "https://{}/api/v1/accounts/verify_credentials",
+            instance.hostname
+        ))
+        .header(
+            header::AUTHORIZATION,
+            format!("Bearer {}", resp.access_token),
+        )
+        .send()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to request to Mastodon instance");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to request to Mastodon instance",
+            )
+        })?
+        .json::<VerifyCredentialsResp>()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to parse Mastodon response");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to parse Mastodon response",
+            )
+        })?;
+
+    Ok((resp.id, resp.username))
+}
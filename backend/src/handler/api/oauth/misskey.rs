@@ -0,0 +1,166 @@
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{config::CONFIG, entity::instance};
+
+/// Prefix of `LOGIN_SESSION` cookies of logins through Misskey, followed by the client ID.
+pub(super) const LOGIN_SESSION_PREFIX: &str = "misskey_";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppCreateReq {
+    name: String,
+    description: String,
+    permission: Vec<String>,
+    callback_url: Url,
+}
+
+#[derive(Deserialize)]
+struct AppCreateResp {
+    id: String,
+    secret: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionGenerateReq {
+    app_secret: String,
+}
+
+#[derive(Deserialize)]
+struct SessionGenerateResp {
+    url: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UserkeyReq {
+    app_secret: String,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct MisskeyUser {
+    id: String,
+    username: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserkeyResp {
+    user: MisskeyUser,
+}
+
+/// Registers the site as an app without any permission, and returns its ID and secret.
+pub(super) async fn create_app(
+    http_client: &reqwest::Client,
+    hostname: &str,
+    redirect_url: Url,
+) -> Result<(String, String), (StatusCode, &'static str)> {
+    let resp = http_client
+        .post(format!("https://{}/api/app/create", hostname))
+        .json(&AppCreateReq {
+            name: CONFIG.site_name.clone(),
+            description: "contest controller".to_string(),
+            permission: Vec::new(),
+            callback_url: redirect_url,
+        })
+        .send()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to request to Misskey instance");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to request to Misskey instance",
+            )
+        })?
+        .json::<AppCreateResp>()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to parse Misskey response");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to parse Misskey response",
+            )
+        })?;
+
+    Ok((resp.id, resp.secret))
+}
+
+/// Starts an authentication session, and returns the `LOGIN_SESSION` cookie value and the URL
+/// the user approves the login at.
+pub(super) async fn start_login(
+    http_client: &reqwest::Client,
+    instance: instance::Model,
+) -> Result<(String, String), (StatusCode, &'static str)> {
+    let resp = http_client
+        .post(format!(
+            "https://{}/api/auth/session/generate",
+            instance.hostname
+        ))
+        .json(&SessionGenerateReq {
+            app_secret: instance.client_secret,
+        })
+        .send()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to request to Misskey instance");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to request to Misskey instance",
+            )
+        })?
+        .json::<SessionGenerateResp>()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to parse Misskey response");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to parse Misskey response",
+            )
+        })?;
+
+    Ok((
+        format!("{}{}", LOGIN_SESSION_PREFIX, instance.client_id),
+        resp.url,
+    ))
+}
+
+/// Exchanges the token the user was redirected back with, and returns the ID and username of the
+/// account which logged in.
+pub(super) async fn finish_login(
+    http_client: &reqwest::Client,
+    instance: &instance::Model,
+    token: String,
+) -> Result<(String, String), (StatusCode, &'static str)> {
+    let resp = http_client
+        .post(format!(
+            "https://{}/api/auth/session/userkey",
+            instance.hostname
+        ))
+        .json(&UserkeyReq {
+            app_secret: instance.client_secret.clone(),
+            token,
+        })
+        .send()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to request to Misskey instance");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to request to Misskey instance",
+            )
+        })?
+        .json::<UserkeyResp>()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "failed to parse Misskey response");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "failed to parse Misskey response",
+            )
+        })?;
+
+    Ok((resp.user.id, resp.user.username))
+}
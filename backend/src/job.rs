@@ -0,0 +1,308 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Context, Result};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend,
+    EntityTrait, PaginatorTrait, QueryFilter, Statement,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tokio::task::JoinError;
+
+use crate::{
+    derivative,
+    entity::job::{self, JobStatus},
+    misskey::post_note,
+    storage::Storage,
+};
+
+/// How long the worker waits before looking for due jobs again when there were none.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Jobs failing this many times are given up.
+const MAX_ATTEMPTS: i32 = 8;
+/// Running jobs not finished in this many seconds are assumed lost with their worker, and run
+/// again.
+const LOCK_TIMEOUT_SECS: i64 = 600;
+
+/// Work done in the background instead of in request handlers, so it can be retried.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Job {
+    /// Generates the derivatives missing for every page of the submission.
+    #[serde(rename_all = "camelCase")]
+    GenerateDerivatives { submission_id: i32 },
+    /// Computes the perceptual hashes of the images of a submission and finds the ones resembling
+    /// images of other submissions.
+    #[serde(rename_all = "camelCase")]
+    HashImages { submission_id: i32 },
+    /// Posts a note to the configured Misskey account.
+    PostNote { text: String },
+}
+
+impl Job {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::GenerateDerivatives { .. } => "generateDerivatives",
+            Self::HashImages { .. } => "hashImages",
+            Self::PostNote { .. } => "postNote",
+        }
+    }
+}
+
+/// Records the job to be run as soon as possible. Enqueueing in the transaction making the
+/// change the job follows up on means the job runs if and only if the change is committed.
+pub async fn enqueue<C>(conn: &C, job: &Job) -> Result<()>
+where
+    C: ConnectionTrait,
+{
+    job::ActiveModel {
+        kind: ActiveValue::Set(job.kind().to_string()),
+        payload: ActiveValue::Set(serde_json::to_value(job).context("failed to serialize job")?),
+        ..Default::default()
+    }
+    .insert(conn)
+    .await
+    .context("failed to insert to database")?;
+    Ok(())
+}
+
+/// Records the job unless the same one is already waiting or running, for jobs enqueued by
+/// requests anyone can repeat.
+pub async fn enqueue_unless_queued<C>(conn: &C, job: &Job) -> Result<()>
+where
+    C: ConnectionTrait,
+{
+    let queued = job::Entity::find()
+        .filter(job::Column::Kind.eq(job.kind()))
+        .filter(
+            job::Column::Payload.eq(serde_json::to_value(job).context("failed to serialize job")?),
+        )
+        .filter(job::Column::Status.is_in([JobStatus::Pending, JobStatus::Running]))
+        .count(conn)
+        .await
+        .context("failed to query database")?;
+    if queued == 0 {
+        enqueue(conn, job).await?;
+    }
+    Ok(())
+}
+
+/// Starts a worker running due jobs one at a time. Workers of several server instances claim
+/// jobs without running any twice. Each job runs in a task of its own, so a job panicking fails
+/// like one returning an error instead of stopping the worker.
+pub fn spawn_worker(
+    db: DatabaseConnection,
+    storage: Arc<dyn Storage>,
+    http_client: reqwest::Client,
+) {
+    tokio::spawn(async move {
+        loop {
+            match claim(&db).await {
+                Ok(Some(job)) => {
+                    let result = tokio::spawn(run(
+                        db.clone(),
+                        storage.clone(),
+                        http_client.clone(),
+                        job.payload.clone(),
+                    ))
+                    .await
+                    .unwrap_or_else(|err| Err(join_error(err)));
+                    finish(&db, job, result).await;
+                }
+                Ok(None) => tokio::time::sleep(POLL_INTERVAL).await,
+                Err(err) => {
+                    tracing::error!(?err, "failed to claim job");
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    });
+}
+
+/// Marks the next due job as running and counts the attempt. Jobs whose lock timed out on their
+/// last attempt, such as ones crashing their worker every time, are given up instead.
+async fn claim(db: &DatabaseConnection) -> Result<Option<job::Model>> {
+    db.execute(Statement::from_string(
+        DbBackend::Postgres,
+        format!(
+            r#"UPDATE "job"
+            SET "status" = 'failed', "locked_at" = NULL, "finished_at" = now(),
+                "last_error" = 'worker lost while running the job'
+            WHERE "status" = 'running' AND "locked_at" < now() - interval '{} seconds'
+                AND "attempts" >= {}"#,
+            LOCK_TIMEOUT_SECS, MAX_ATTEMPTS,
+        ),
+    ))
+    .await
+    .context("failed to update database")?;
+
+    job::Entity::find()
+        .from_raw_sql(Statement::from_string(
+            DbBackend::Postgres,
+            format!(
+                r#"UPDATE "job"
+                SET "status" = 'running', "locked_at" = now(), "attempts" = "attempts" + 1
+                WHERE "id" = (
+                    SELECT "id" FROM "job"
+                    WHERE ("status" = 'pending' AND "run_at" <= now())
+                        OR ("status" = 'running' AND "locked_at" < now() - interval '{} seconds'
+                            AND "attempts" < {})
+                    ORDER BY "run_at", "id"
+                    LIMIT 1
+                    FOR UPDATE SKIP LOCKED
+                )
+                RETURNING *"#,
+                LOCK_TIMEOUT_SECS, MAX_ATTEMPTS,
+            ),
+        ))
+        .one(db)
+        .await
+        .context("failed to update database")
+}
+
+async fn run(
+    db: DatabaseConnection,
+    storage: Arc<dyn Storage>,
+    http_client: reqwest::Client,
+    payload: serde_json::Value,
+) -> Result<()> {
+    match serde_json::from_value::<Job>(payload) {
+        Ok(Job::GenerateDerivatives { submission_id }) => {
+            derivative::generate_missing(&db, &*storage, submission_id).await
+        }
+        Ok(Job::HashImages { submission_id }) => {
+            derivative::hash_images(&db, &*storage, submission_id).await
+        }
+        Ok(Job::PostNote { text }) => post_note(&http_client, text).await,
+        Err(err) => Err(err).context("failed to deserialize job"),
+    }
+}
+
+/// Turns a job task that panicked or was cancelled into the error of the attempt.
+fn join_error(err: JoinError) -> anyhow::Error {
+    match err.try_into_panic() {
+        Ok(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+                .unwrap_or("unknown panic");
+            anyhow::anyhow!("job panicked: {}", message)
+        }
+        Err(err) => anyhow::Error::new(err).context("job cancelled"),
+    }
+}
+
+/// Records the result of the attempt, scheduling a retry if the job failed and has attempts
+/// left.
+async fn finish(db: &DatabaseConnection, job: job::Model, result: Result<()>) {
+    let now = OffsetDateTime::now_utc();
+    let mut job_activemodel = job::ActiveModel {
+        id: ActiveValue::Unchanged(job.id),
+        locked_at: ActiveValue::Set(None),
+        ..Default::default()
+    };
+    match result {
+        Ok(()) => {
+            tracing::info!(id = job.id, kind = job.kind, "job succeeded");
+            job_activemodel.status = ActiveValue::Set(JobStatus::Succeeded);
+            job_activemodel.finished_at = ActiveValue::Set(Some(now));
+        }
+        Err(err) if job.attempts < MAX_ATTEMPTS => {
+            let backoff = retry_backoff(job.attempts);
+            tracing::warn!(
+                ?err,
+                id = job.id,
+                kind = job.kind,
+                attempts = job.attempts,
+                "job failed, retrying in {:?}",
+                backoff
+            );
+            job_activemodel.status = ActiveValue::Set(JobStatus::Pending);
+            job_activemodel.last_error = ActiveValue::Set(Some(format!("{:#}", err)));
+            job_activemodel.run_at = ActiveValue::Set(now + backoff);
+        }
+        Err(err) => {
+            tracing::error!(
+                ?err,
+                id = job.id,
+                kind = job.kind,
+                attempts = job.attempts,
+                "job failed, giving up"
+            );
+            job_activemodel.status = ActiveValue::Set(JobStatus::Failed);
+            job_activemodel.last_error = ActiveValue::Set(Some(format!("{:#}", err)));
+            job_activemodel.finished_at = ActiveValue::Set(Some(now));
+        }
+    }
+
+    // The job is run again once its lock times out if this fails.
+    if let Err(err) = job_activemodel.update(db).await {
+        tracing::error!(?err, id = job.id, "failed to update job");
+    }
+}
+
+/// Delay before the next attempt, doubling from 30 seconds up to an hour.
+fn retry_backoff(attempts: i32) -> Duration {
+    let exponent = attempts.clamp(1, 8) as u32 - 1;
+    Duration::from_secs(30 * 2u64.pow(exponent)).min(Duration::from_secs(60 * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_backoff_doubles_up_to_an_hour() {
+        let backoffs = (0..=10)
+            .map(|attempts| retry_backoff(attempts).as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            backoffs,
+            [30, 30, 60, 120, 240, 480, 960, 1920, 3600, 3600, 3600],
+        );
+        assert_eq!(retry_backoff(i32::MAX), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn job_payloads_are_stable() {
+        // Payloads outlive deploys in the job table, so their format must not change.
+        for (job, json) in [
+            (
+                Job::GenerateDerivatives { submission_id: 1 },
+                serde_json::json!({ "kind": "generateDerivatives", "submissionId": 1 }),
+            ),
+            (
+                Job::HashImages { submission_id: 2 },
+                serde_json::json!({ "kind": "hashImages", "submissionId": 2 }),
+            ),
+            (
+                Job::PostNote {
+                    text: "hello".to_string(),
+                },
+                serde_json::json!({ "kind": "postNote", "text": "hello" }),
+            ),
+        ] {
+            assert_eq!(serde_json::to_value(&job).unwrap(), json);
+            assert_eq!(json["kind"], job.kind());
+            assert_eq!(serde_json::from_value::<Job>(json).unwrap(), job);
+        }
+    }
+
+    #[test]
+    fn unknown_jobs_are_rejected() {
+        assert!(serde_json::from_value::<Job>(serde_json::json!({ "kind": "nope" })).is_err());
+        assert!(
+            serde_json::from_value::<Job>(serde_json::json!({ "kind": "hashImages" })).is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn panics_become_errors() {
+        let err = tokio::spawn(async { panic!("boom") }).await.unwrap_err();
+        assert_eq!(join_error(err).to_string(), "job panicked: boom");
+
+        let err = tokio::spawn(async { panic!("{}", 1) }).await.unwrap_err();
+        assert_eq!(join_error(err).to_string(), "job panicked: 1");
+    }
+}
@@ -1,10 +1,21 @@
+use anyhow::Context;
 use migration::MigratorTrait;
-use sea_orm::Database;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, Database, DatabaseConnection, EntityTrait,
+    QueryFilter,
+};
+
+use crate::{config::CONFIG, entity::contest};
 
 mod config;
+mod derivative;
 mod entity;
 mod handler;
+mod job;
+mod media;
 mod misskey;
+mod storage;
+mod text;
 mod utils;
 
 async fn shutdown_signal() {
@@ -33,6 +44,65 @@ async fn shutdown_signal() {
     tracing::info!("signal received, starting graceful shutdown");
 }
 
+async fn upsert_configured_contest(db: &DatabaseConnection) -> anyhow::Result<()> {
+    let (
+        Some(name),
+        Some(submission_open_at),
+        Some(submission_close_at),
+        Some(voting_open_at),
+        Some(voting_close_at),
+    ) = (
+        &CONFIG.contest_name,
+        CONFIG.submission_open_at,
+        CONFIG.submission_close_at,
+        CONFIG.voting_open_at,
+        CONFIG.voting_close_at,
+    )
+    else {
+        tracing::info!("contest not configured, skipping contest upsert");
+        return Ok(());
+    };
+
+    let existing_contest = contest::Entity::find()
+        .filter(contest::Column::Slug.eq(&CONFIG.contest_slug))
+        .one(db)
+        .await
+        .context("failed to query database")?;
+
+    let contest_activemodel = contest::ActiveModel {
+        id: existing_contest
+            .map(|contest| ActiveValue::Unchanged(contest.id))
+            .unwrap_or(ActiveValue::NotSet),
+        slug: ActiveValue::Set(CONFIG.contest_slug.clone()),
+        name: ActiveValue::Set(name.clone()),
+        submission_open_at: ActiveValue::Set(submission_open_at),
+        submission_close_at: ActiveValue::Set(submission_close_at),
+        voting_open_at: ActiveValue::Set(voting_open_at),
+        voting_close_at: ActiveValue::Set(voting_close_at),
+        categories: ActiveValue::Set(CONFIG.contest_categories.clone().unwrap_or_else(|| {
+            CONFIG
+                .categories
+                .iter()
+                .map(|category| category.slug.clone())
+                .collect()
+        })),
+        voting_method: ActiveValue::Set(CONFIG.voting_method),
+        tie_break: ActiveValue::Set(CONFIG.tie_break),
+        blind: ActiveValue::Set(CONFIG.blind),
+        judging_open_at: ActiveValue::Set(CONFIG.judging_open_at),
+        judging_close_at: ActiveValue::Set(CONFIG.judging_close_at),
+        jury_weight: ActiveValue::Set(CONFIG.jury_weight),
+    };
+
+    contest_activemodel
+        .save(db)
+        .await
+        .context("failed to save contest to database")?;
+    tracing::info!(slug = %CONFIG.contest_slug, "contest upserted");
+
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt()
@@ -42,19 +112,32 @@ async fn main() -> anyhow::Result<()> {
 
     let db = Database::connect(format!(
         "postgresql://{}:{}@{}:{}/{}",
-        config::CONFIG.database_user,
-        config::CONFIG.database_password,
-        config::CONFIG.database_host,
-        config::CONFIG.database_port,
-        config::CONFIG.database_database,
+        CONFIG.database_user,
+        CONFIG.database_password,
+        CONFIG.database_host,
+        CONFIG.database_port,
+        CONFIG.database_database,
     ))
     .await?;
 
     migration::Migrator::up(&db, None).await?;
 
-    let router = crate::handler::create_router(db);
+    let storage = storage::from_config()?;
+
+    match std::env::args().nth(1).as_deref() {
+        None => {}
+        Some("migrate-storage") => {
+            return storage::migrate_database_blobs(&db, &*storage).await;
+        }
+        Some(command) => anyhow::bail!("unknown command: {}", command),
+    }
+
+    upsert_configured_contest(&db).await?;
+
+    job::spawn_worker(db.clone(), storage.clone(), reqwest::Client::new());
+    let router = crate::handler::create_router(db, storage);
 
-    let listen_addr = &crate::config::CONFIG.listen_addr;
+    let listen_addr = &CONFIG.listen_addr;
     tracing::info!(%listen_addr, "starting http server...");
     axum::Server::bind(&listen_addr.parse()?)
         .serve(router.into_make_service())
@@ -0,0 +1,141 @@
+use std::{borrow::Cow, io::Cursor};
+
+use anyhow::{Context, Result};
+use image::{imageops::FilterType, DynamicImage, GenericImageView, ImageOutputFormat};
+
+use crate::{config::ImageSize, entity::submission_derivative::DerivativeFormat};
+
+mod metadata;
+
+pub use metadata::strip_metadata;
+
+/// Quality of lossy WebP derivatives, from 0 to 100.
+const WEBP_QUALITY: f32 = 80.0;
+
+/// Image formats accepted for submissions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Webp,
+    Gif,
+}
+
+impl ImageFormat {
+    /// Detects the format from the magic bytes at the start of the data, regardless of what the
+    /// client claims it is.
+    pub fn sniff(data: &[u8]) -> Option<Self> {
+        if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some(Self::Png)
+        } else if data.starts_with(b"\xff\xd8\xff") {
+            Some(Self::Jpeg)
+        } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+            Some(Self::Webp)
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some(Self::Gif)
+        } else {
+            None
+        }
+    }
+
+    pub fn mime(self) -> mime::Mime {
+        match self {
+            Self::Png => mime::IMAGE_PNG,
+            Self::Jpeg => mime::IMAGE_JPEG,
+            Self::Webp => "image/webp".parse().unwrap(),
+            Self::Gif => mime::IMAGE_GIF,
+        }
+    }
+
+    fn to_image_format(self) -> image::ImageFormat {
+        match self {
+            Self::Png => image::ImageFormat::Png,
+            Self::Jpeg => image::ImageFormat::Jpeg,
+            Self::Webp => image::ImageFormat::WebP,
+            Self::Gif => image::ImageFormat::Gif,
+        }
+    }
+}
+
+/// Reads the width and height from the image header without decoding the pixels.
+pub fn image_dimensions(data: &[u8], format: ImageFormat) -> Result<(u32, u32)> {
+    image::io::Reader::with_format(Cursor::new(data), format.to_image_format())
+        .into_dimensions()
+        .context("failed to read image dimensions")
+}
+
+/// Decodes the image, or the first frame of an animated one, turned upright according to its
+/// EXIF orientation.
+pub fn decode_image(data: &[u8], format: ImageFormat) -> Result<DynamicImage> {
+    let image = image::load_from_memory_with_format(data, format.to_image_format())
+        .context("failed to decode image")?;
+    let orientation = match format {
+        ImageFormat::Jpeg => metadata::jpeg_orientation(data),
+        _ => None,
+    };
+
+    Ok(match orientation {
+        Some(2) => image.fliph(),
+        Some(3) => image.rotate180(),
+        Some(4) => image.flipv(),
+        Some(5) => image.rotate90().fliph(),
+        Some(6) => image.rotate90(),
+        Some(7) => image.rotate270().fliph(),
+        Some(8) => image.rotate270(),
+        _ => image,
+    })
+}
+
+/// Scales the image down to fit in the size, keeping its aspect ratio. Smaller images are never
+/// scaled up.
+pub fn fit_image<'a>(image: &'a DynamicImage, size: &ImageSize) -> Cow<'a, DynamicImage> {
+    let (width, height) = image.dimensions();
+    if width > size.max_width || height > size.max_height {
+        Cow::Owned(image.resize(size.max_width, size.max_height, FilterType::Lanczos3))
+    } else {
+        Cow::Borrowed(image)
+    }
+}
+
+/// Difference hash of the image, which stays close for resized, recompressed or slightly edited
+/// copies. Each bit tells whether a pixel of a 9×8 grayscale thumbnail is brighter than the one
+/// to its right.
+pub fn perceptual_hash(image: &DynamicImage) -> u64 {
+    let thumbnail = image.thumbnail_exact(9, 8).to_luma8();
+    let mut hash = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if thumbnail.get_pixel(x, y)[0] > thumbnail.get_pixel(x + 1, y)[0] {
+                hash |= 1;
+            }
+        }
+    }
+    hash
+}
+
+/// Number of bits differing between two perceptual hashes, from 0 for identical images to 64.
+pub fn perceptual_hash_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+pub fn encode_derivative(image: &DynamicImage, format: DerivativeFormat) -> Result<Vec<u8>> {
+    let image = image.to_rgba8();
+
+    match format {
+        DerivativeFormat::Webp => {
+            Ok(
+                webp::Encoder::from_rgba(image.as_raw(), image.width(), image.height())
+                    .encode(WEBP_QUALITY)
+                    .to_vec(),
+            )
+        }
+        DerivativeFormat::Png => {
+            let mut data = Cursor::new(Vec::new());
+            DynamicImage::ImageRgba8(image)
+                .write_to(&mut data, ImageOutputFormat::Png)
+                .context("failed to encode PNG")?;
+            Ok(data.into_inner())
+        }
+    }
+}
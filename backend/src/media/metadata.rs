@@ -0,0 +1,603 @@
+use std::borrow::Cow;
+
+use anyhow::{bail, Context, Result};
+
+use super::ImageFormat;
+
+/// Removes EXIF, XMP and IPTC metadata and comments, which can reveal locations, devices and
+/// software, without re-encoding the image. Color profiles and animation are kept, and so is the
+/// EXIF orientation of JPEG images as the only tag, so photos are still displayed upright.
+///
+/// The stripped image is returned as pieces to be written in order, mostly borrowed from the
+/// data, so that it is never copied whole.
+pub fn strip_metadata(data: &[u8], format: ImageFormat) -> Result<Vec<Cow<'_, [u8]>>> {
+    match format {
+        ImageFormat::Png => strip_png(data),
+        ImageFormat::Jpeg => strip_jpeg(data),
+        ImageFormat::Webp => strip_webp(data),
+        ImageFormat::Gif => strip_gif(data),
+    }
+    .context("malformed image")
+}
+
+/// EXIF orientation of a JPEG image, from 1 to 8.
+pub fn jpeg_orientation(data: &[u8]) -> Option<u16> {
+    let mut orientation = None;
+    for_each_jpeg_segment(data, |marker, payload| {
+        if marker == APP1 && orientation.is_none() {
+            if let Some(tiff) = payload.strip_prefix(EXIF_HEADER) {
+                orientation = tiff_orientation(tiff);
+            }
+        }
+        Ok(())
+    })
+    .ok()?;
+    orientation.filter(|orientation| (1..=8).contains(orientation))
+}
+
+fn read_u16_be(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32_be(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn read_u32_le(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+fn strip_png(data: &[u8]) -> Result<Vec<Cow<'_, [u8]>>> {
+    let mut stripped = vec![Cow::Borrowed(PNG_SIGNATURE)];
+    let mut offset = PNG_SIGNATURE.len();
+    loop {
+        let length = read_u32_be(data, offset).context("truncated chunk")? as usize;
+        let chunk_type = data
+            .get(offset + 4..offset + 8)
+            .context("truncated chunk")?;
+        // Length, type, data and CRC.
+        let end = offset + 12 + length;
+        let chunk = data.get(offset..end).context("truncated chunk")?;
+        if !matches!(chunk_type, b"eXIf" | b"tEXt" | b"zTXt" | b"iTXt" | b"tIME") {
+            stripped.push(Cow::Borrowed(chunk));
+        }
+        if chunk_type == b"IEND" {
+            return Ok(stripped);
+        }
+        offset = end;
+    }
+}
+
+const APP1: u8 = 0xe1;
+const APP2: u8 = 0xe2;
+const SOS: u8 = 0xda;
+const EOI: u8 = 0xd9;
+const EXIF_HEADER: &[u8] = b"Exif\0\0";
+
+/// Calls the closure with the marker and payload of every segment up to the end of the image.
+/// Entropy-coded scan data and anything after the end of the image, such as the extra images
+/// of MPF files, are skipped.
+fn for_each_jpeg_segment<'a>(
+    data: &'a [u8],
+    mut f: impl FnMut(u8, &'a [u8]) -> Result<()>,
+) -> Result<()> {
+    if !data.starts_with(b"\xff\xd8") {
+        bail!("missing start of image");
+    }
+    let mut offset = 2;
+    loop {
+        if data.get(offset) != Some(&0xff) {
+            bail!("missing marker");
+        }
+        // Markers may be padded with any number of fill bytes.
+        while data.get(offset + 1) == Some(&0xff) {
+            offset += 1;
+        }
+        let marker = *data.get(offset + 1).context("truncated marker")?;
+        offset += 2;
+        match marker {
+            EOI => return Ok(()),
+            0x01 | 0xd0..=0xd7 => {
+                f(marker, &[])?;
+                continue;
+            }
+            _ => {}
+        }
+
+        let length = read_u16_be(data, offset).context("truncated segment")? as usize;
+        let payload = data
+            .get(offset + 2..offset + length)
+            .context("truncated segment")?;
+        f(marker, payload)?;
+        offset += length;
+
+        if marker == SOS {
+            // Scan data runs until the next marker other than a stuffed zero or a restart.
+            let scan_end = (offset..data.len().saturating_sub(1))
+                .find(|&i| data[i] == 0xff && !matches!(data[i + 1], 0x00 | 0xd0..=0xd7 | 0xff))
+                .context("truncated scan")?;
+            f(0x00, &data[offset..scan_end])?;
+            offset = scan_end;
+        }
+    }
+}
+
+fn strip_jpeg(data: &[u8]) -> Result<Vec<Cow<'_, [u8]>>> {
+    let orientation = jpeg_orientation(data);
+
+    let mut stripped = vec![Cow::Borrowed(&b"\xff\xd8"[..])];
+    // Directly after the JFIF segment if any, as it has to come first.
+    let mut orientation_position = 1;
+    for_each_jpeg_segment(data, |marker, payload| {
+        match marker {
+            // Scan data following the previous start of scan segment.
+            0x00 => stripped.push(Cow::Borrowed(payload)),
+            0x01 | 0xd0..=0xd7 => stripped.push(Cow::Owned(vec![0xff, marker])),
+            // EXIF and XMP, with the orientation written back below.
+            APP1 => {}
+            // Only color profiles are kept, not MPF or FlashPix data.
+            APP2 if !payload.starts_with(b"ICC_PROFILE\0") => {}
+            // Vendor and IPTC segments other than JFIF and Adobe color transforms, and comments.
+            0xe3..=0xed | 0xef | 0xfe => {}
+            _ => {
+                let first = stripped.len() == 1;
+                let mut header = vec![0xff, marker];
+                header.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+                stripped.push(Cow::Owned(header));
+                stripped.push(Cow::Borrowed(payload));
+                if first && marker == 0xe0 {
+                    orientation_position = stripped.len();
+                }
+            }
+        }
+        Ok(())
+    })?;
+    stripped.push(Cow::Owned(vec![0xff, EOI]));
+
+    if let Some(orientation) = orientation.filter(|&orientation| orientation != 1) {
+        stripped.insert(
+            orientation_position,
+            Cow::Owned(orientation_segment(orientation)),
+        );
+    }
+
+    Ok(stripped)
+}
+
+/// APP1 segment of a big-endian EXIF block with the orientation as its only tag.
+fn orientation_segment(orientation: u16) -> Vec<u8> {
+    let mut segment = vec![0xff, APP1, 0, 34];
+    segment.extend_from_slice(EXIF_HEADER);
+    // TIFF header with the first IFD right after it.
+    segment.extend_from_slice(b"MM\0\x2a\0\0\0\x08");
+    // One entry: tag, SHORT type, count of one and the value padded to four bytes.
+    segment.extend_from_slice(&1u16.to_be_bytes());
+    segment.extend_from_slice(&[0x01, 0x12, 0x00, 0x03, 0x00, 0x00, 0x00, 0x01]);
+    segment.extend_from_slice(&orientation.to_be_bytes());
+    segment.extend_from_slice(&[0, 0]);
+    // No next IFD.
+    segment.extend_from_slice(&[0, 0, 0, 0]);
+    segment
+}
+
+/// Orientation tag of the first IFD of a TIFF block.
+fn tiff_orientation(tiff: &[u8]) -> Option<u16> {
+    let read_u16 = |offset: usize| -> Option<u16> {
+        let bytes = tiff.get(offset..offset + 2)?.try_into().ok()?;
+        match &tiff[..2] {
+            b"II" => Some(u16::from_le_bytes(bytes)),
+            b"MM" => Some(u16::from_be_bytes(bytes)),
+            _ => None,
+        }
+    };
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes = tiff.get(offset..offset + 4)?.try_into().ok()?;
+        match &tiff[..2] {
+            b"II" => Some(u32::from_le_bytes(bytes)),
+            b"MM" => Some(u32::from_be_bytes(bytes)),
+            _ => None,
+        }
+    };
+
+    if tiff.len() < 8 || read_u16(2)? != 42 {
+        return None;
+    }
+    let ifd = read_u32(4)? as usize;
+    let count = read_u16(ifd)? as usize;
+    (0..count)
+        .map(|i| ifd + 2 + i * 12)
+        .find(|&entry| read_u16(entry) == Some(0x0112) && read_u16(entry + 2) == Some(3))
+        .and_then(|entry| read_u16(entry + 8))
+}
+
+fn strip_webp(data: &[u8]) -> Result<Vec<Cow<'_, [u8]>>> {
+    if data.len() < 12 || !data.starts_with(b"RIFF") || &data[8..12] != b"WEBP" {
+        bail!("missing RIFF header");
+    }
+    let riff_end = read_u32_le(data, 4).unwrap() as usize + 8;
+    // Only the padding of the last chunk may be missing.
+    if riff_end > data.len() + 1 {
+        bail!("truncated file");
+    }
+    let riff_end = riff_end.min(data.len());
+
+    // Header with the RIFF size filled in below.
+    let mut stripped = vec![Cow::Borrowed(&[][..])];
+    let mut offset = 12;
+    while offset < riff_end {
+        let fourcc = data.get(offset..offset + 4).context("truncated chunk")?;
+        let size = read_u32_le(data, offset + 4).context("truncated chunk")? as usize;
+        // Chunks are padded to an even size.
+        let end = offset + 8 + size + size % 2;
+        let chunk = data
+            .get(offset..end)
+            .or_else(|| data.get(offset..offset + 8 + size))
+            .context("truncated chunk")?;
+        match fourcc {
+            b"EXIF" | b"XMP " => {}
+            b"VP8X" if size >= 1 => {
+                let mut chunk = chunk.to_vec();
+                // Clear the EXIF and XMP flags.
+                chunk[8] &= !0x0c;
+                stripped.push(Cow::Owned(chunk));
+            }
+            _ => stripped.push(Cow::Borrowed(chunk)),
+        }
+        offset = end;
+    }
+
+    let riff_size = 4 + stripped.iter().map(|piece| piece.len()).sum::<usize>() as u32;
+    let mut header = b"RIFF".to_vec();
+    header.extend_from_slice(&riff_size.to_le_bytes());
+    header.extend_from_slice(b"WEBP");
+    stripped[0] = Cow::Owned(header);
+    Ok(stripped)
+}
+
+fn strip_gif(data: &[u8]) -> Result<Vec<Cow<'_, [u8]>>> {
+    // Header and logical screen descriptor, followed by the global color table if any.
+    let packed = *data.get(10).context("truncated header")?;
+    let mut offset = 13;
+    if packed & 0x80 != 0 {
+        offset += 3 << ((packed & 0x07) + 1);
+    }
+    let mut stripped = vec![Cow::Borrowed(
+        data.get(..offset).context("truncated header")?,
+    )];
+
+    // End of the data sub-blocks starting at the offset.
+    let sub_blocks_end = |mut offset: usize| -> Result<usize> {
+        loop {
+            let size = *data.get(offset).context("truncated block")? as usize;
+            offset += 1 + size;
+            if size == 0 {
+                return Ok(offset);
+            }
+        }
+    };
+
+    loop {
+        match *data.get(offset).context("missing trailer")? {
+            0x21 => {
+                let label = *data.get(offset + 1).context("truncated extension")?;
+                let end = sub_blocks_end(offset + 2)?;
+                let keep = match label {
+                    // Comments.
+                    0xfe => false,
+                    // Application extensions other than animation looping, such as XMP.
+                    0xff => matches!(
+                        data.get(offset + 2..offset + 14),
+                        Some(b"\x0bNETSCAPE2.0" | b"\x0bANIMEXTS1.0")
+                    ),
+                    _ => true,
+                };
+                if keep {
+                    stripped.push(Cow::Borrowed(data.get(offset..end).unwrap()));
+                }
+                offset = end;
+            }
+            0x2c => {
+                // Image descriptor, local color table, LZW minimum code size and image data.
+                let packed = *data.get(offset + 9).context("truncated image")?;
+                let mut data_start = offset + 10;
+                if packed & 0x80 != 0 {
+                    data_start += 3 << ((packed & 0x07) + 1);
+                }
+                let end = sub_blocks_end(data_start + 1)?;
+                stripped.push(Cow::Borrowed(
+                    data.get(offset..end).context("truncated image")?,
+                ));
+                offset = end;
+            }
+            0x3b => {
+                stripped.push(Cow::Borrowed(&data[offset..=offset]));
+                return Ok(stripped);
+            }
+            _ => bail!("unknown block"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{DynamicImage, GenericImageView, ImageOutputFormat, RgbaImage};
+
+    use super::*;
+
+    /// Text which only appears in the metadata of the fixtures.
+    const SECRET: &[u8] = b"secret location";
+
+    fn contains(data: &[u8], needle: &[u8]) -> bool {
+        data.windows(needle.len()).any(|window| window == needle)
+    }
+
+    /// 3×2 image, so that rotations show in its dimensions.
+    fn encode(format: ImageOutputFormat) -> Vec<u8> {
+        let image = DynamicImage::ImageRgba8(RgbaImage::from_fn(3, 2, |x, y| {
+            image::Rgba([x as u8 * 80, y as u8 * 120, 200, 255])
+        }));
+        let mut data = Cursor::new(Vec::new());
+        image.write_to(&mut data, format).unwrap();
+        data.into_inner()
+    }
+
+    fn strip(data: &[u8], format: ImageFormat) -> Result<Vec<u8>> {
+        strip_metadata(data, format).map(|stripped| stripped.concat())
+    }
+
+    fn decode(data: &[u8], format: ImageFormat) -> DynamicImage {
+        crate::media::decode_image(data, format).unwrap()
+    }
+
+    /// Stripping a truncated or corrupted image either fails or succeeds, but never panics, and
+    /// truncated ones fail unless only their trailing padding is missing.
+    fn assert_rejects_malformed(data: &[u8], format: ImageFormat) {
+        for len in 0..data.len() - 1 {
+            assert!(strip(&data[..len], format).is_err(), "{}", len);
+        }
+        for i in 0..data.len() {
+            for byte in [0x00, 0x01, 0x7f, 0xff] {
+                let mut corrupted = data.to_vec();
+                corrupted[i] = byte;
+                let _ = strip(&corrupted, format);
+            }
+        }
+    }
+
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = !0u32;
+        for &byte in data {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+            }
+        }
+        !crc
+    }
+
+    fn png_chunk(chunk_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        chunk.extend_from_slice(chunk_type);
+        chunk.extend_from_slice(data);
+        chunk.extend_from_slice(&crc32(&chunk[4..]).to_be_bytes());
+        chunk
+    }
+
+    fn png_fixture() -> Vec<u8> {
+        let data = encode(ImageOutputFormat::Png);
+        // After the signature and the IHDR chunk.
+        let ihdr_end = PNG_SIGNATURE.len() + 12 + 13;
+        let mut chunks = png_chunk(b"sRGB", &[0]);
+        chunks.extend(png_chunk(b"tEXt", &[b"Comment\0", SECRET].concat()));
+        chunks.extend(png_chunk(b"zTXt", &[b"Comment\0\0", SECRET].concat()));
+        chunks.extend(png_chunk(
+            b"iTXt",
+            &[b"XML:com.adobe.xmp\0\0\0\0\0", SECRET].concat(),
+        ));
+        chunks.extend(png_chunk(
+            b"eXIf",
+            &[b"MM\0\x2a\0\0\0\x08", SECRET].concat(),
+        ));
+        chunks.extend(png_chunk(b"tIME", &[0x07, 0xe7, 1, 1, 0, 0, 0]));
+        [&data[..ihdr_end], &chunks, &data[ihdr_end..]].concat()
+    }
+
+    #[test]
+    fn strip_png() {
+        let data = png_fixture();
+        let stripped = strip(&data, ImageFormat::Png).unwrap();
+        assert!(!contains(&stripped, SECRET));
+        for chunk_type in [b"tEXt", b"zTXt", b"iTXt", b"eXIf", b"tIME"] {
+            assert!(!contains(&stripped, chunk_type));
+        }
+        assert!(contains(&stripped, b"sRGB"));
+        assert_eq!(
+            decode(&stripped, ImageFormat::Png).to_rgba8(),
+            decode(&data, ImageFormat::Png).to_rgba8(),
+        );
+    }
+
+    #[test]
+    fn strip_png_malformed() {
+        assert_rejects_malformed(&png_fixture(), ImageFormat::Png);
+    }
+
+    fn jpeg_segment(marker: u8, payload: &[u8]) -> Vec<u8> {
+        let mut segment = vec![0xff, marker];
+        segment.extend_from_slice(&(payload.len() as u16 + 2).to_be_bytes());
+        segment.extend_from_slice(payload);
+        segment
+    }
+
+    /// Little-endian EXIF block with the orientation and a camera make pointing to [`SECRET`].
+    fn exif(orientation: u16) -> Vec<u8> {
+        let mut exif = EXIF_HEADER.to_vec();
+        exif.extend_from_slice(b"II\x2a\0\x08\0\0\0");
+        exif.extend_from_slice(&2u16.to_le_bytes());
+        // Make, ASCII, stored after the IFD.
+        exif.extend_from_slice(&0x010fu16.to_le_bytes());
+        exif.extend_from_slice(&2u16.to_le_bytes());
+        exif.extend_from_slice(&(SECRET.len() as u32).to_le_bytes());
+        exif.extend_from_slice(&(8 + 2 + 2 * 12 + 4u32).to_le_bytes());
+        // Orientation, SHORT.
+        exif.extend_from_slice(&0x0112u16.to_le_bytes());
+        exif.extend_from_slice(&3u16.to_le_bytes());
+        exif.extend_from_slice(&1u32.to_le_bytes());
+        exif.extend_from_slice(&orientation.to_le_bytes());
+        exif.extend_from_slice(&[0, 0]);
+        // No next IFD.
+        exif.extend_from_slice(&[0, 0, 0, 0]);
+        exif.extend_from_slice(SECRET);
+        exif
+    }
+
+    fn jpeg_fixture(orientation: u16) -> Vec<u8> {
+        let data = encode(ImageOutputFormat::Jpeg(90));
+        // After the start of image and the JFIF segment.
+        assert_eq!(&data[2..4], b"\xff\xe0");
+        let jfif_end = 4 + read_u16_be(&data, 4).unwrap() as usize;
+        let mut segments = jpeg_segment(APP1, &exif(orientation));
+        segments.extend(jpeg_segment(
+            APP1,
+            &[b"http://ns.adobe.com/xap/1.0/\0", SECRET].concat(),
+        ));
+        segments.extend(jpeg_segment(0xed, &[b"Photoshop 3.0\0", SECRET].concat()));
+        segments.extend(jpeg_segment(0xfe, SECRET));
+        [&data[..jfif_end], &segments, &data[jfif_end..]].concat()
+    }
+
+    #[test]
+    fn strip_jpeg() {
+        let data = jpeg_fixture(6);
+        let stripped = strip(&data, ImageFormat::Jpeg).unwrap();
+        assert!(!contains(&stripped, SECRET));
+        assert!(!contains(&stripped, b"Photoshop"));
+        assert!(stripped[2..].starts_with(b"\xff\xe0"));
+        assert_eq!(jpeg_orientation(&stripped), Some(6));
+        // Turned upright by the kept orientation.
+        assert_eq!(decode(&stripped, ImageFormat::Jpeg).dimensions(), (2, 3));
+        assert_eq!(
+            decode(&stripped, ImageFormat::Jpeg).to_rgba8(),
+            decode(&data, ImageFormat::Jpeg).to_rgba8(),
+        );
+    }
+
+    #[test]
+    fn strip_jpeg_default_orientation() {
+        let stripped = strip(&jpeg_fixture(1), ImageFormat::Jpeg).unwrap();
+        assert!(!contains(&stripped, EXIF_HEADER));
+        assert_eq!(jpeg_orientation(&stripped), None);
+        assert_eq!(decode(&stripped, ImageFormat::Jpeg).dimensions(), (3, 2));
+    }
+
+    #[test]
+    fn strip_jpeg_malformed() {
+        assert_rejects_malformed(&jpeg_fixture(6), ImageFormat::Jpeg);
+    }
+
+    fn webp_chunk(fourcc: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let mut chunk = fourcc.to_vec();
+        chunk.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(data);
+        if data.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    /// Extended WebP with EXIF and XMP chunks after the image.
+    fn webp_fixture() -> Vec<u8> {
+        let image = [[80, 120, 200, 255]; 6].concat();
+        let simple = webp::Encoder::from_rgba(&image, 3, 2)
+            .encode_lossless()
+            .to_vec();
+        let mut vp8x = vec![0x08 | 0x04, 0, 0, 0];
+        vp8x.extend_from_slice(&2u32.to_le_bytes()[..3]);
+        vp8x.extend_from_slice(&1u32.to_le_bytes()[..3]);
+
+        let mut chunks = webp_chunk(b"VP8X", &vp8x);
+        chunks.extend_from_slice(&simple[12..]);
+        chunks.extend(webp_chunk(b"EXIF", &exif(6)[EXIF_HEADER.len()..]));
+        chunks.extend(webp_chunk(b"XMP ", SECRET));
+        let mut data = b"RIFF".to_vec();
+        data.extend_from_slice(&(chunks.len() as u32 + 4).to_le_bytes());
+        data.extend_from_slice(b"WEBP");
+        data.extend(chunks);
+        data
+    }
+
+    #[test]
+    fn strip_webp() {
+        let data = webp_fixture();
+        let stripped = strip(&data, ImageFormat::Webp).unwrap();
+        assert!(!contains(&stripped, SECRET));
+        assert!(!contains(&stripped, b"EXIF"));
+        assert!(!contains(&stripped, b"XMP "));
+        assert_eq!(stripped[20] & 0x0c, 0);
+        assert_eq!(
+            read_u32_le(&stripped, 4).unwrap() as usize + 8,
+            stripped.len()
+        );
+        assert_eq!(
+            decode(&stripped, ImageFormat::Webp).to_rgba8(),
+            decode(&data, ImageFormat::Webp).to_rgba8(),
+        );
+    }
+
+    #[test]
+    fn strip_webp_malformed() {
+        assert_rejects_malformed(&webp_fixture(), ImageFormat::Webp);
+    }
+
+    /// Data sub-blocks of at most 255 bytes, ending with an empty one.
+    fn gif_sub_blocks(data: &[u8]) -> Vec<u8> {
+        let mut sub_blocks = Vec::new();
+        for chunk in data.chunks(255) {
+            sub_blocks.push(chunk.len() as u8);
+            sub_blocks.extend_from_slice(chunk);
+        }
+        sub_blocks.push(0);
+        sub_blocks
+    }
+
+    fn gif_fixture() -> Vec<u8> {
+        let data = encode(ImageOutputFormat::Gif);
+        let (trailer, data) = data.split_last().unwrap();
+        assert_eq!(*trailer, 0x3b);
+        let mut extensions = b"\x21\xfe".to_vec();
+        extensions.extend(gif_sub_blocks(SECRET));
+        extensions.extend_from_slice(b"\x21\xff\x0bXMP DataXMP");
+        extensions.extend(gif_sub_blocks(SECRET));
+        extensions.extend_from_slice(b"\x21\xff\x0bNETSCAPE2.0");
+        extensions.extend(gif_sub_blocks(&[1, 0, 0]));
+        [data, &extensions, &[0x3b]].concat()
+    }
+
+    #[test]
+    fn strip_gif() {
+        let data = gif_fixture();
+        let stripped = strip(&data, ImageFormat::Gif).unwrap();
+        assert!(!contains(&stripped, SECRET));
+        assert!(!contains(&stripped, b"XMP DataXMP"));
+        assert!(contains(&stripped, b"NETSCAPE2.0"));
+        assert_eq!(
+            decode(&stripped, ImageFormat::Gif).to_rgba8(),
+            decode(&data, ImageFormat::Gif).to_rgba8(),
+        );
+    }
+
+    #[test]
+    fn strip_gif_malformed() {
+        assert_rejects_malformed(&gif_fixture(), ImageFormat::Gif);
+    }
+}
@@ -0,0 +1,161 @@
+use std::{io, ops::RangeInclusive, path::Path, pin::Pin, sync::Arc};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use axum::body::Bytes;
+use futures_util::Stream;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, TransactionTrait,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::{StorageBackend, CONFIG},
+    entity::submission,
+    job::{self, Job},
+    media::ImageFormat,
+};
+
+mod local;
+mod s3;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+/// Content of a stored object, read as it is sent.
+pub type ByteStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send>>;
+
+/// Blob store for submission images, addressed by keys made of lowercase letters, digits and `/`.
+#[async_trait]
+pub trait Storage: std::fmt::Debug + Send + Sync {
+    async fn put(&self, key: &str, data: Bytes) -> Result<()>;
+    /// Puts the content of the file, without reading it into memory if the backend can.
+    async fn put_file(&self, key: &str, path: &Path) -> Result<()> {
+        let data = tokio::fs::read(path).await.context("failed to read file")?;
+        self.put(key, data.into()).await
+    }
+    /// Returns `None` if nothing is stored under the key.
+    async fn get(&self, key: &str) -> Result<Option<Bytes>>;
+    /// Size in bytes of what is stored under the key, or `None` if nothing is.
+    async fn size(&self, key: &str) -> Result<Option<u64>>;
+    /// Streams what is stored under the key, or only the inclusive byte range of it which must
+    /// be within its size, along with the length of the stream. Returns `None` if nothing is
+    /// stored under the key.
+    async fn get_stream(
+        &self,
+        key: &str,
+        range: Option<RangeInclusive<u64>>,
+    ) -> Result<Option<(u64, ByteStream)>>;
+    /// Deleting a missing key is not an error.
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+pub fn from_config() -> Result<Arc<dyn Storage>> {
+    Ok(match CONFIG.storage_backend {
+        StorageBackend::Local => Arc::new(LocalStorage::new(CONFIG.storage_path.clone())),
+        StorageBackend::S3 => Arc::new(S3Storage::new(
+            reqwest::Client::new(),
+            CONFIG.s3_endpoint.clone().context("S3 endpoint not set")?,
+            CONFIG.s3_bucket.clone().context("S3 bucket not set")?,
+            CONFIG.s3_region.clone(),
+            CONFIG
+                .s3_access_key
+                .clone()
+                .context("S3 access key not set")?,
+            CONFIG
+                .s3_secret_key
+                .clone()
+                .context("S3 secret key not set")?,
+        )),
+    })
+}
+
+/// Unique key for a new submission image, ending with the name such as `original`.
+pub fn new_image_key(name: &str) -> String {
+    let id = random_string::generate(32, "abcdefghijklmnopqrstuvwxyz0123456789");
+    format!("submission/{}/{}", id, name)
+}
+
+/// Hex-encoded SHA-256 of the content, which doubles as its strong ETag.
+pub fn content_hash(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Moves images still stored in the database to the storage backend, one submission at a time so
+/// that only a single image is held in memory. Thumbnails are dropped and a job is enqueued to
+/// generate derivatives from the image instead. Each submission is updated in a transaction of
+/// its own, and its image is put under a key derived from its ID, so running again after an
+/// interruption overwrites what was put before instead of leaving it orphaned.
+pub async fn migrate_database_blobs(db: &DatabaseConnection, storage: &dyn Storage) -> Result<()> {
+    let ids = submission::Entity::find()
+        .select_only()
+        .column(submission::Column::Id)
+        .filter(
+            submission::Column::Data
+                .is_not_null()
+                .or(submission::Column::ThumbnailData.is_not_null()),
+        )
+        .order_by_asc(submission::Column::Id)
+        .into_tuple::<i32>()
+        .all(db)
+        .await
+        .context("failed to query database")?;
+    tracing::info!(count = ids.len(), "migrating submission images to storage");
+
+    for id in ids {
+        let tx = db.begin().await.context("failed to begin transaction")?;
+
+        // Locking the submission keeps it from being replaced while its image is moved.
+        let Some(submission) = submission::Entity::find_by_id(id)
+            .lock_exclusive()
+            .one(&tx)
+            .await
+            .context("failed to query database")?
+        else {
+            continue;
+        };
+
+        let mut submission_activemodel = submission::ActiveModel {
+            id: ActiveValue::Unchanged(submission.id),
+            ..Default::default()
+        };
+        if let Some(data) = submission.data {
+            if submission.data_hash.is_none() {
+                submission_activemodel.data_hash = ActiveValue::Set(Some(content_hash(&data)));
+            }
+            if submission.mime_type.is_none() {
+                submission_activemodel.mime_type = ActiveValue::Set(
+                    ImageFormat::sniff(&data).map(|image_format| image_format.mime().to_string()),
+                );
+            }
+            let data_key = format!("submission/legacy{}/original", submission.id);
+            storage
+                .put(&data_key, data.into())
+                .await
+                .context("failed to put image to storage")?;
+            submission_activemodel.data = ActiveValue::Set(None);
+            submission_activemodel.data_key = ActiveValue::Set(Some(data_key));
+        }
+        if submission.thumbnail_data.is_some() {
+            submission_activemodel.thumbnail_data = ActiveValue::Set(None);
+            submission_activemodel.thumbnail_hash = ActiveValue::Set(None);
+        }
+
+        submission_activemodel
+            .update(&tx)
+            .await
+            .context("failed to update database")?;
+        job::enqueue(
+            &tx,
+            &Job::GenerateDerivatives {
+                submission_id: submission.id,
+            },
+        )
+        .await?;
+        tx.commit().await.context("failed to commit to database")?;
+        tracing::info!(id, "submission images migrated");
+    }
+
+    Ok(())
+}
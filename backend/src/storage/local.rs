@@ -0,0 +1,241 @@
+use std::{
+    io::{ErrorKind, SeekFrom},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use axum::body::Bytes;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use super::{ByteStream, Storage};
+
+#[derive(Debug)]
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Creates the parent directory of the destination, and returns the path to write next to
+    /// it first, so readers never see a partial file.
+    async fn prepare(&self, path: &Path) -> Result<PathBuf> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .context("failed to create directory")?;
+        }
+        Ok(path.with_extension("tmp"))
+    }
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+    async fn put(&self, key: &str, data: Bytes) -> Result<()> {
+        let path = self.root.join(key);
+        let tmp_path = self.prepare(&path).await?;
+        tokio::fs::write(&tmp_path, &data)
+            .await
+            .context("failed to write file")?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .context("failed to rename file")?;
+
+        Ok(())
+    }
+
+    async fn put_file(&self, key: &str, src: &Path) -> Result<()> {
+        let path = self.root.join(key);
+        let tmp_path = self.prepare(&path).await?;
+        tokio::fs::copy(src, &tmp_path)
+            .await
+            .context("failed to copy file")?;
+        tokio::fs::rename(&tmp_path, &path)
+            .await
+            .context("failed to rename file")?;
+
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Bytes>> {
+        match tokio::fs::read(self.root.join(key)).await {
+            Ok(data) => Ok(Some(data.into())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).context("failed to read file"),
+        }
+    }
+
+    async fn size(&self, key: &str) -> Result<Option<u64>> {
+        match tokio::fs::metadata(self.root.join(key)).await {
+            Ok(metadata) => Ok(Some(metadata.len())),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).context("failed to read file metadata"),
+        }
+    }
+
+    async fn get_stream(
+        &self,
+        key: &str,
+        range: Option<RangeInclusive<u64>>,
+    ) -> Result<Option<(u64, ByteStream)>> {
+        let mut file = match tokio::fs::File::open(self.root.join(key)).await {
+            Ok(file) => file,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err).context("failed to open file"),
+        };
+        let len = match range {
+            Some(range) => {
+                file.seek(SeekFrom::Start(*range.start()))
+                    .await
+                    .context("failed to seek file")?;
+                range.end() - range.start() + 1
+            }
+            None => file
+                .metadata()
+                .await
+                .context("failed to read file metadata")?
+                .len(),
+        };
+
+        Ok(Some((len, Box::pin(ReaderStream::new(file.take(len))))))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.root.join(key)).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err).context("failed to remove file"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::TryStreamExt;
+
+    use super::*;
+
+    async fn collect(stream: ByteStream) -> Vec<u8> {
+        stream
+            .try_fold(Vec::new(), |mut data, chunk| async move {
+                data.extend_from_slice(&chunk);
+                Ok(data)
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn put_get_delete() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(root.path().to_path_buf());
+        let key = "submission/abc/original";
+
+        assert_eq!(storage.get(key).await.unwrap(), None);
+        assert_eq!(storage.size(key).await.unwrap(), None);
+        assert!(storage.get_stream(key, None).await.unwrap().is_none());
+
+        storage
+            .put(key, Bytes::from_static(b"first"))
+            .await
+            .unwrap();
+        assert_eq!(storage.get(key).await.unwrap().unwrap(), "first");
+        storage
+            .put(key, Bytes::from_static(b"replaced"))
+            .await
+            .unwrap();
+        assert_eq!(storage.get(key).await.unwrap().unwrap(), "replaced");
+        assert_eq!(storage.size(key).await.unwrap(), Some(8));
+
+        storage.delete(key).await.unwrap();
+        assert_eq!(storage.get(key).await.unwrap(), None);
+        // Deleting again is not an error.
+        storage.delete(key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn put_file_copies() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(root.path().to_path_buf());
+        let src = root.path().join("upload");
+        std::fs::write(&src, b"uploaded").unwrap();
+
+        storage
+            .put_file("submission/abc/original", &src)
+            .await
+            .unwrap();
+        assert_eq!(
+            storage
+                .get("submission/abc/original")
+                .await
+                .unwrap()
+                .unwrap(),
+            "uploaded"
+        );
+        // The source is left for its owner to remove.
+        assert!(src.exists());
+    }
+
+    #[tokio::test]
+    async fn puts_leave_no_temporary_files() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(root.path().to_path_buf());
+        let src = root.path().join("upload");
+        std::fs::write(&src, b"uploaded").unwrap();
+
+        storage
+            .put("submission/abc/original", Bytes::from_static(b"data"))
+            .await
+            .unwrap();
+        storage
+            .put_file("submission/abc/small", &src)
+            .await
+            .unwrap();
+
+        let mut names = std::fs::read_dir(root.path().join("submission/abc"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["original", "small"]);
+    }
+
+    #[tokio::test]
+    async fn prepare_writes_next_to_destination() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(root.path().to_path_buf());
+        let path = root.path().join("submission/abc/original");
+
+        let tmp_path = storage.prepare(&path).await.unwrap();
+        assert_eq!(tmp_path, root.path().join("submission/abc/original.tmp"));
+        assert!(root.path().join("submission/abc").is_dir());
+    }
+
+    #[tokio::test]
+    async fn get_stream_ranges() {
+        let root = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(root.path().to_path_buf());
+        let key = "submission/abc/original";
+        storage
+            .put(key, Bytes::from_static(b"0123456789"))
+            .await
+            .unwrap();
+
+        let (len, stream) = storage.get_stream(key, None).await.unwrap().unwrap();
+        assert_eq!(len, 10);
+        assert_eq!(collect(stream).await, b"0123456789");
+
+        let (len, stream) = storage.get_stream(key, Some(3..=5)).await.unwrap().unwrap();
+        assert_eq!(len, 3);
+        assert_eq!(collect(stream).await, b"345");
+
+        let (len, stream) = storage.get_stream(key, Some(9..=9)).await.unwrap().unwrap();
+        assert_eq!(len, 1);
+        assert_eq!(collect(stream).await, b"9");
+    }
+}
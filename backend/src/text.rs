@@ -0,0 +1,144 @@
+use std::borrow::Cow;
+
+use ammonia::Builder;
+use once_cell::sync::Lazy;
+use pulldown_cmark::{escape::escape_html, html, Event, Options, Parser, Tag};
+
+use crate::entity::submission::TextFormat;
+
+/// Sanitizer for rendered Markdown. Images are removed so that texts cannot load anything from
+/// other servers when they are read.
+static SANITIZER: Lazy<Builder<'static>> = Lazy::new(|| {
+    let mut builder = Builder::default();
+    builder.rm_tags(["img"]);
+    builder
+});
+
+/// Renders the text to HTML that is safe to insert into a page.
+pub fn render_html(text: &str, format: TextFormat) -> String {
+    match format {
+        TextFormat::Plain => {
+            // Paragraphs are separated by blank lines.
+            let mut html = String::with_capacity(text.len());
+            let mut in_paragraph = false;
+            for line in text.lines() {
+                if line.trim().is_empty() {
+                    if in_paragraph {
+                        html.push_str("</p>\n");
+                        in_paragraph = false;
+                    }
+                    continue;
+                }
+                html.push_str(if in_paragraph { "<br>\n" } else { "<p>" });
+                in_paragraph = true;
+                escape_html(&mut html, line).unwrap();
+            }
+            if in_paragraph {
+                html.push_str("</p>\n");
+            }
+            html
+        }
+        TextFormat::Markdown => {
+            let mut unsafe_html = String::with_capacity(text.len());
+            html::push_html(&mut unsafe_html, parse_markdown(text));
+            SANITIZER.clean(&unsafe_html).to_string()
+        }
+    }
+}
+
+/// Text as shown to readers without any markup, for counting against the length limit.
+pub fn text_content(text: &str, format: TextFormat) -> Cow<'_, str> {
+    match format {
+        TextFormat::Plain => Cow::Borrowed(text),
+        TextFormat::Markdown => {
+            let mut content = String::with_capacity(text.len());
+            for event in parse_markdown(text) {
+                match event {
+                    Event::Text(text) | Event::Code(text) => content.push_str(&text),
+                    Event::SoftBreak | Event::HardBreak => content.push('\n'),
+                    Event::End(
+                        Tag::Paragraph
+                        | Tag::Heading(..)
+                        | Tag::BlockQuote
+                        | Tag::CodeBlock(_)
+                        | Tag::Item,
+                    ) => content.push('\n'),
+                    _ => {}
+                }
+            }
+            content.truncate(content.trim_end().len());
+            Cow::Owned(content)
+        }
+    }
+}
+
+/// Parses CommonMark with strikethrough. Raw HTML is shown as written instead of being
+/// interpreted.
+fn parse_markdown(text: &str) -> impl Iterator<Item = Event<'_>> {
+    Parser::new_ext(text, Options::ENABLE_STRIKETHROUGH).map(|event| match event {
+        Event::Html(html) => Event::Text(html),
+        event => event,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_html_is_escaped() {
+        let html = render_html(
+            "<script>alert(1)</script>\n\nHello <b onclick=\"x()\">world</b>",
+            TextFormat::Markdown,
+        );
+        assert!(!html.contains("<script"), "{html}");
+        assert!(!html.contains("<b"), "{html}");
+        assert!(
+            html.contains("&lt;script&gt;alert(1)&lt;/script&gt;"),
+            "{html}"
+        );
+        assert!(html.contains("Hello &lt;b onclick="), "{html}");
+    }
+
+    #[test]
+    fn javascript_links_lose_href() {
+        let html = render_html("[x](javascript:alert(1))", TextFormat::Markdown);
+        assert!(!html.contains("javascript"), "{html}");
+        assert!(!html.contains("href"), "{html}");
+        assert!(html.contains(">x</a>"), "{html}");
+
+        let html = render_html("[x](https://example.com/)", TextFormat::Markdown);
+        assert!(html.contains("href=\"https://example.com/\""), "{html}");
+    }
+
+    #[test]
+    fn images_are_removed() {
+        let html = render_html("![a](https://evil/x.png)", TextFormat::Markdown);
+        assert!(!html.contains("<img"), "{html}");
+        assert!(!html.contains("evil"), "{html}");
+    }
+
+    #[test]
+    fn plain_text_is_escaped_and_split_into_paragraphs() {
+        assert_eq!(
+            render_html("a < b & c\nd\n\n\n<i>e</i>\n", TextFormat::Plain),
+            "<p>a &lt; b &amp; c<br>\nd</p>\n<p>&lt;i&gt;e&lt;/i&gt;</p>\n",
+        );
+        assert_eq!(render_html("", TextFormat::Plain), "");
+        assert_eq!(render_html(" \n\n", TextFormat::Plain), "");
+    }
+
+    #[test]
+    fn text_content_drops_markup() {
+        assert_eq!(text_content("**a** _b_", TextFormat::Markdown), "a b");
+        assert_eq!(
+            text_content(
+                "# T\n\n- x\n- `y`\n\n[l](https://example.com/)",
+                TextFormat::Markdown
+            ),
+            "T\nx\ny\nl",
+        );
+        assert_eq!(text_content("<i>a</i>", TextFormat::Markdown), "<i>a</i>");
+        assert_eq!(text_content("**a** _b_", TextFormat::Plain), "**a** _b_");
+    }
+}
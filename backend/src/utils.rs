@@ -1,4 +1,5 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use axum::http::header;
 use serde::Deserialize;
 use url::Url;
 
@@ -23,6 +24,13 @@ struct NodeInfo {
     software: NodeInfoSoftware,
 }
 
+/// Nodeinfo versions with the software name where it is expected.
+const NODEINFO_SCHEMAS: &[&str] = &[
+    "http://nodeinfo.diaspora.software/ns/schema/2.0",
+    "http://nodeinfo.diaspora.software/ns/schema/2.1",
+];
+
+/// Name of the software the instance runs, from its nodeinfo.
 pub async fn detect_instance(http_client: &reqwest::Client, url: Url) -> Result<Option<String>> {
     let nodeinfometa_url = url.join("/.well-known/nodeinfo")?;
 
@@ -35,7 +43,7 @@ pub async fn detect_instance(http_client: &reqwest::Client, url: Url) -> Result<
     let nodeinfolink = nodeinfometa
         .links
         .into_iter()
-        .find(|link| link.rel == "http://nodeinfo.diaspora.software/ns/schema/2.0");
+        .find(|link| NODEINFO_SCHEMAS.contains(&link.rel.as_str()));
 
     if let Some(link) = nodeinfolink {
         let nodeinfo: NodeInfo = http_client.get(link.href).send().await?.json().await?;
@@ -44,3 +52,328 @@ pub async fn detect_instance(http_client: &reqwest::Client, url: Url) -> Result<
         Ok(None)
     }
 }
+
+#[derive(Deserialize)]
+struct JrdLink {
+    rel: String,
+    href: Option<Url>,
+}
+
+/// JSON resource descriptor returned by WebFinger.
+#[derive(Deserialize)]
+struct Jrd {
+    subject: String,
+    #[serde(default)]
+    links: Vec<JrdLink>,
+}
+
+/// Account found through WebFinger.
+#[derive(Debug)]
+struct WebFingerAccount {
+    username: String,
+    /// Domain of the canonical `username@domain` handle.
+    domain: String,
+    /// Host serving the actor of the account, and the API of its instance.
+    api_host: String,
+}
+
+impl TryFrom<Jrd> for WebFingerAccount {
+    type Error = anyhow::Error;
+
+    fn try_from(jrd: Jrd) -> Result<Self> {
+        let (username, domain) = jrd
+            .subject
+            .strip_prefix("acct:")
+            .and_then(|acct| acct.rsplit_once('@'))
+            .context("subject is not an acct URI")?;
+        let actor_url = jrd
+            .links
+            .into_iter()
+            .find(|link| link.rel == "self")
+            .and_then(|link| link.href)
+            .context("actor link not found")?;
+
+        Ok(Self {
+            username: username.to_string(),
+            domain: parse_host(domain)?,
+            api_host: host_of(&actor_url)?,
+        })
+    }
+}
+
+/// Looks up an `acct:` URI or a profile URL on the host with WebFinger.
+async fn webfinger(
+    http_client: &reqwest::Client,
+    host: &str,
+    resource: &str,
+) -> Result<WebFingerAccount> {
+    let jrd: Jrd = http_client
+        .get(format!("https://{}/.well-known/webfinger", host))
+        .query(&[("resource", resource)])
+        .header(header::ACCEPT, "application/jrd+json, application/json")
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    jrd.try_into()
+}
+
+/// Host serving the API for the account handle, profile URL or domain entered to log in. Falls
+/// back to the domain of the handle or URL if WebFinger does not find the account.
+pub async fn resolve_api_host(http_client: &reqwest::Client, input: &str) -> Result<String> {
+    let input = input.trim();
+    let (host, resource) = if input.starts_with("https://") || input.starts_with("http://") {
+        let url = Url::parse(input)?;
+        (host_of(&url)?, url.to_string())
+    } else if let Some((username, domain)) = input.trim_start_matches('@').rsplit_once('@') {
+        let host = parse_host(domain)?;
+        let resource = format!("acct:{}@{}", username, host);
+        (host, resource)
+    } else {
+        return parse_host(input);
+    };
+
+    match webfinger(http_client, &host, &resource).await {
+        Ok(account) => Ok(account.api_host),
+        Err(err) => {
+            tracing::warn!(?err, resource, "failed to look up account with WebFinger");
+            Ok(host)
+        }
+    }
+}
+
+/// Domain of the canonical handle of the account logged in on the host, which differs from the
+/// host when the instance serves the accounts of another domain. Falls back to the host if
+/// WebFinger does not find the account, but fails if the other domain does not point back at it.
+pub async fn resolve_account_domain(
+    http_client: &reqwest::Client,
+    username: &str,
+    api_host: &str,
+) -> Result<String> {
+    let resource = format!("acct:{}@{}", username, api_host);
+    let account = match webfinger(http_client, api_host, &resource).await {
+        Ok(account) => account,
+        Err(err) => {
+            tracing::warn!(?err, resource, "failed to look up account with WebFinger");
+            return Ok(api_host.to_owned());
+        }
+    };
+    let canonical =
+        if account.domain == api_host || !account.username.eq_ignore_ascii_case(username) {
+            None
+        } else {
+            let resource = format!("acct:{}@{}", account.username, account.domain);
+            match webfinger(http_client, &account.domain, &resource).await {
+                Ok(canonical) => Some(canonical),
+                Err(err) => {
+                    tracing::warn!(?err, resource, "failed to look up account with WebFinger");
+                    return Ok(api_host.to_owned());
+                }
+            }
+        };
+
+    account_domain(username, api_host, &account, canonical.as_ref())
+}
+
+/// Checks the account the API host returns for the username, and the account returned by its
+/// domain if it is another one, which has to point back at the host, so that instances cannot
+/// claim the accounts of other domains. Falls back to the host if it returns another account.
+fn account_domain(
+    username: &str,
+    api_host: &str,
+    account: &WebFingerAccount,
+    canonical: Option<&WebFingerAccount>,
+) -> Result<String> {
+    if !account.username.eq_ignore_ascii_case(username) {
+        tracing::warn!(
+            username,
+            subject = account.username,
+            "WebFinger subject does not match"
+        );
+        return Ok(api_host.to_owned());
+    }
+    if account.domain == api_host {
+        return Ok(account.domain.clone());
+    }
+
+    let canonical = canonical.context("canonical account not looked up")?;
+    anyhow::ensure!(
+        canonical.username.eq_ignore_ascii_case(&account.username)
+            && canonical.domain == account.domain,
+        "subject does not match {}@{}",
+        account.username,
+        account.domain
+    );
+    anyhow::ensure!(
+        canonical.api_host == api_host,
+        "{} does not point back at {}",
+        account.domain,
+        api_host
+    );
+
+    Ok(account.domain.clone())
+}
+
+/// Normalizes a bare `host[:port]`, rejecting anything else.
+fn parse_host(s: &str) -> Result<String> {
+    let url = Url::parse(&format!("https://{}", s))?;
+    anyhow::ensure!(
+        url.username().is_empty() && url.path() == "/" && url.query().is_none(),
+        "not a host: {}",
+        s
+    );
+    host_of(&url)
+}
+
+fn host_of(url: &Url) -> Result<String> {
+    let host = url.host_str().context("URL without host")?;
+    Ok(match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(subject: &str, actor_url: &str) -> WebFingerAccount {
+        let jrd = serde_json::from_value::<Jrd>(serde_json::json!({
+            "subject": subject,
+            "links": [
+                { "rel": "http://webfinger.net/rel/profile-page", "href": "https://example.com/@alice" },
+                { "rel": "self", "type": "application/activity+json", "href": actor_url },
+            ],
+        }))
+        .unwrap();
+        jrd.try_into().unwrap()
+    }
+
+    #[test]
+    fn parse_jrd() {
+        let account = account(
+            "acct:alice@example.com",
+            "https://social.example.com/users/alice",
+        );
+        assert_eq!(account.username, "alice");
+        assert_eq!(account.domain, "example.com");
+        assert_eq!(account.api_host, "social.example.com");
+    }
+
+    #[test]
+    fn parse_jrd_without_actor() {
+        for links in [
+            serde_json::json!([]),
+            serde_json::json!([{ "rel": "http://webfinger.net/rel/profile-page", "href": "https://example.com/@alice" }]),
+            serde_json::json!([{ "rel": "self", "type": "application/activity+json" }]),
+        ] {
+            let jrd = serde_json::from_value::<Jrd>(serde_json::json!({
+                "subject": "acct:alice@example.com",
+                "links": links,
+            }))
+            .unwrap();
+            assert!(WebFingerAccount::try_from(jrd).is_err());
+        }
+    }
+
+    #[test]
+    fn parse_jrd_without_acct_subject() {
+        for subject in [
+            "https://example.com/@alice",
+            "acct:alice",
+            "acct:alice@example.com/path",
+        ] {
+            let jrd = serde_json::from_value::<Jrd>(serde_json::json!({
+                "subject": subject,
+                "links": [{ "rel": "self", "href": "https://example.com/users/alice" }],
+            }))
+            .unwrap();
+            assert!(WebFingerAccount::try_from(jrd).is_err(), "{}", subject);
+        }
+    }
+
+    #[test]
+    fn account_domain_on_api_host() {
+        let local = account("acct:Alice@example.com", "https://example.com/users/alice");
+        assert_eq!(
+            account_domain("alice", "example.com", &local, None).unwrap(),
+            "example.com"
+        );
+    }
+
+    #[test]
+    fn account_domain_falls_back_on_mismatched_subject() {
+        let local = account(
+            "acct:mallory@example.com",
+            "https://example.com/users/mallory",
+        );
+        assert_eq!(
+            account_domain("alice", "example.com", &local, None).unwrap(),
+            "example.com"
+        );
+        let delegated = account(
+            "acct:mallory@example.net",
+            "https://example.com/users/mallory",
+        );
+        assert_eq!(
+            account_domain("alice", "example.com", &delegated, None).unwrap(),
+            "example.com"
+        );
+    }
+
+    #[test]
+    fn account_domain_delegated() {
+        let local = account(
+            "acct:alice@example.com",
+            "https://social.example.com/users/alice",
+        );
+        let canonical = account(
+            "acct:alice@example.com",
+            "https://social.example.com/users/alice",
+        );
+        assert_eq!(
+            account_domain("alice", "social.example.com", &local, Some(&canonical)).unwrap(),
+            "example.com"
+        );
+        assert!(account_domain("alice", "social.example.com", &local, None).is_err());
+    }
+
+    #[test]
+    fn account_domain_delegated_elsewhere() {
+        let local = account(
+            "acct:alice@example.com",
+            "https://evil.example.net/users/alice",
+        );
+        // The domain delegates to another host than the one claiming its account.
+        let canonical = account(
+            "acct:alice@example.com",
+            "https://social.example.com/users/alice",
+        );
+        assert!(account_domain("alice", "evil.example.net", &local, Some(&canonical)).is_err());
+    }
+
+    #[test]
+    fn account_domain_delegated_with_mismatched_subject() {
+        let local = account(
+            "acct:alice@example.com",
+            "https://social.example.com/users/alice",
+        );
+        for subject in ["acct:bob@example.com", "acct:alice@example.net"] {
+            let canonical = account(subject, "https://social.example.com/users/alice");
+            assert!(
+                account_domain("alice", "social.example.com", &local, Some(&canonical)).is_err(),
+                "{}",
+                subject
+            );
+        }
+    }
+
+    #[test]
+    fn parse_hosts() {
+        assert_eq!(parse_host("Example.COM").unwrap(), "example.com");
+        assert_eq!(parse_host("example.com:8443").unwrap(), "example.com:8443");
+        assert!(parse_host("user@example.com").is_err());
+        assert!(parse_host("example.com/path").is_err());
+    }
+}
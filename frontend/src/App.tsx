@@ -1,21 +1,23 @@
 import { QueryClient, QueryClientProvider } from "react-query";
 import { BrowserRouter, Route, Routes } from "react-router-dom";
 
-import ArtListView from "./ArtListView";
-import ArtResultView from "./ArtResultView";
-import ArtSubmitView from "./ArtSubmitView";
-import ArtView from "./ArtView";
 import { AuthRequired } from "./AuthRequired";
 import { createClient } from "./Axios";
 import { AxiosClientProvider } from "./AxiosContext";
-import LiteratureListView from "./LiteratureListView";
-import LiteratureResultView from "./LiteratureResultView";
-import LiteratureSubmitView from "./LiteratureSubmitView";
-import LiteratureView from "./LiteratureView";
+import { CategoryRequired } from "./CategoryRequired";
+import ContestListView from "./ContestListView";
+import ImageListView from "./ImageListView";
+import ImageResultView from "./ImageResultView";
+import ImageSubmitView from "./ImageSubmitView";
+import ImageView from "./ImageView";
 import LoginView from "./LoginView";
 import MainView from "./MainView";
 import NavBar from "./NavBar";
 import NotFoundView from "./NotFoundView";
+import TextListView from "./TextListView";
+import TextResultView from "./TextResultView";
+import TextSubmitView from "./TextSubmitView";
+import TextView from "./TextView";
 
 const queryClient = new QueryClient();
 const axiosClient = createClient();
@@ -27,36 +29,50 @@ function App() {
         <BrowserRouter>
           <Routes>
             <Route element={<NavBar />}>
-              <Route path="/" element={<MainView />} />
+              <Route path="/" element={<ContestListView />} />
 
               <Route path="/login" element={<LoginView />} />
 
-              <Route path="/literature" element={<LiteratureListView />} />
+              <Route path="/contest/:slug" element={<MainView />} />
+
+              <Route
+                path="/contest/:slug/:category"
+                element={
+                  <CategoryRequired
+                    text={<TextListView />}
+                    image={<ImageListView />}
+                  />
+                }
+              />
               <Route
-                path="/literature/submit"
+                path="/contest/:slug/:category/submit"
                 element={
                   <AuthRequired>
-                    <LiteratureSubmitView />
+                    <CategoryRequired
+                      text={<TextSubmitView />}
+                      image={<ImageSubmitView />}
+                    />
                   </AuthRequired>
                 }
               />
               <Route
-                path="/literature/result"
-                element={<LiteratureResultView />}
+                path="/contest/:slug/:category/result"
+                element={
+                  <CategoryRequired
+                    text={<TextResultView />}
+                    image={<ImageResultView />}
+                  />
+                }
               />
-              <Route path="/literature/:id" element={<LiteratureView />} />
-
-              <Route path="/art" element={<ArtListView />} />
               <Route
-                path="/art/submit"
+                path="/contest/:slug/:category/:id"
                 element={
-                  <AuthRequired>
-                    <ArtSubmitView />
-                  </AuthRequired>
+                  <CategoryRequired
+                    text={<TextView />}
+                    image={<ImageView />}
+                  />
                 }
               />
-              <Route path="/art/result" element={<ArtResultView />} />
-              <Route path="/art/:id" element={<ArtView />} />
 
               <Route path="*" element={<NotFoundView />} />
             </Route>
@@ -0,0 +1,12 @@
+interface Authored {
+  authorHandle: string | null;
+  authorInstance: string | null;
+}
+
+/** Account of the author, or a placeholder while authors are hidden. */
+export function formatAuthor(submission: Authored): string {
+  if (submission.authorHandle == null || submission.authorInstance == null) {
+    return "비공개";
+  }
+  return `${submission.authorHandle}@${submission.authorInstance}`;
+}
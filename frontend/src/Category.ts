@@ -0,0 +1,6 @@
+import { createContext, useContext } from "react";
+
+import { Category } from "./HttpTypes";
+
+export const CategoryContext = createContext<Category>(undefined!);
+export const useCategory = (): Category => useContext(CategoryContext);
@@ -0,0 +1,39 @@
+import { ReactNode } from "react";
+import { useParams } from "react-router-dom";
+
+import { CategoryContext } from "./Category";
+import LoadingView from "./LoadingView";
+import NotEnabledView from "./NotEnabledView";
+import NotFoundView from "./NotFoundView";
+import { useEnabled, useRules } from "./QueryHooks";
+
+interface Props {
+  text: ReactNode;
+  image: ReactNode;
+}
+
+/** Renders the view for the kind of the category in the path if enabled. */
+export function CategoryRequired(props: Props) {
+  const { category: slug } = useParams();
+  const { data: enabled, isLoading: isEnabledLoading } = useEnabled();
+  const { data: rules, isLoading: isRulesLoading } = useRules();
+
+  if (isEnabledLoading || isRulesLoading || enabled == null || rules == null) {
+    return <LoadingView />;
+  }
+
+  if (slug == null || enabled[slug] == null) {
+    return <NotFoundView />;
+  }
+
+  const category = rules[slug];
+  if (!enabled[slug] || category == null) {
+    return <NotEnabledView />;
+  }
+
+  return (
+    <CategoryContext.Provider value={category}>
+      {category.kind === "text" ? props.text : props.image}
+    </CategoryContext.Provider>
+  );
+}
@@ -0,0 +1,46 @@
+import { Helmet } from "react-helmet";
+import { Link, Navigate } from "react-router-dom";
+
+import LoadingView from "./LoadingView";
+import { useContests } from "./QueryHooks";
+
+export default function ContestListView() {
+  const { data: contests, isLoading } = useContests();
+
+  if (isLoading || contests == null) {
+    return <LoadingView />;
+  }
+
+  if (contests.length === 1) {
+    return <Navigate to={`/contest/${contests[0].slug}`} replace />;
+  }
+
+  return (
+    <>
+      <Helmet>
+        <title>대회 목록</title>
+      </Helmet>
+      <div className="flex w-full justify-center px-6 pb-10 pt-4">
+        <ul className="w-full md:w-2/3">
+          {contests.map((contest) => (
+            <li key={contest.id} className="p-2">
+              <Link to={`/contest/${contest.slug}`} className="text-lg">
+                {contest.name}
+              </Link>
+              <div className="text-sm">
+                제출 가능 시간:{" "}
+                {new Date(contest.submissionOpenAt).toLocaleString()} ~{" "}
+                {new Date(contest.submissionCloseAt).toLocaleString()}
+              </div>
+              <div className="text-sm">
+                투표 가능 시간:{" "}
+                {new Date(contest.votingOpenAt).toLocaleString()} ~{" "}
+                {new Date(contest.votingCloseAt).toLocaleString()}
+              </div>
+            </li>
+          ))}
+        </ul>
+      </div>
+    </>
+  );
+}
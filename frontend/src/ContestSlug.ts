@@ -0,0 +1,4 @@
+import { useParams } from "react-router-dom";
+
+/** Slug of the contest in the current path, if the page belongs to one. */
+export const useContestSlug = (): string | undefined => useParams().slug;
@@ -1,11 +1,38 @@
-export interface GetEnabledResp {
-  literature: boolean;
-  art: boolean;
+export interface Contest {
+  id: number;
+  slug: string;
+  name: string;
+  submissionOpenAt: string;
+  submissionCloseAt: string;
+  votingOpenAt: string;
+  votingCloseAt: string;
+  categories: string[];
+}
+
+export type GetEnabledResp = Record<string, boolean>;
+
+export interface Category {
+  slug: string;
+  name: string;
+  kind: "text" | "image";
+  titleMaxLength: number;
+  descriptionMaxLength: number;
+  textMaxLength: number;
+  imageMaxSize: number;
+  imageMaxWidth: number;
+  imageMaxHeight: number;
+  altTextMaxLength: number;
+  pageLimit: number;
+  voteLimit: number;
+  submissionLimit: number;
 }
 
+export type GetRulesResp = Record<string, Category>;
+
 export interface User {
   handle: string;
   instance: string;
+  accountId: string;
 }
 
 export interface PostOauthAuthorizeReq {
@@ -22,51 +49,60 @@ export interface GetOpenedResp {
   closeAt: string;
 }
 
-export interface Literature {
-  id: number;
-  title: string;
-  text: string;
-  isNsfw: boolean;
-  authorHandle: string;
-  authorInstance: string;
-}
+export type TextFormat = "plain" | "markdown";
 
-export interface LiteratureMetadata {
+export interface Metadata {
   id: number;
+  category: string;
   title: string;
+  description: string;
   isNsfw: boolean;
-  authorHandle: string;
-  authorInstance: string;
+  mimeType: string | null;
+  altTexts: string[];
+  authorHandle: string | null;
+  authorInstance: string | null;
 }
 
-export interface ArtMetadata {
+export interface TextContent {
   id: number;
+  category: string;
   title: string;
   description: string;
+  text: string | null;
+  format: TextFormat | null;
   isNsfw: boolean;
-  authorHandle: string;
-  authorInstance: string;
+  authorHandle: string | null;
+  authorInstance: string | null;
+  html: string;
 }
 
-export interface PostLiteratureReq {
+export interface PostTextReq {
+  category: string;
   title: string;
+  description: string;
   text: string;
+  format: TextFormat;
   isNsfw: boolean;
 }
 
-export interface PostArtReq {
+export interface PostImageReq {
+  category: string;
   title: string;
   description: string;
   isNsfw: boolean;
-  file: File;
+  files: File[];
+  altTexts: string[];
 }
 
 export interface Vote {
   voted: boolean;
   voteCount: number;
+  score: number | null;
+  rank: number | null;
 }
 
 export interface PostVoteReq {
+  category: string;
   id: number;
 }
 
@@ -75,4 +111,11 @@ export interface GetResultOpenedResp {
   openAt: string;
 }
 
-export type WithVoteCount<M> = { voteCount: number } & M;
+export type Standing = {
+  rank: number;
+  voteCount: number;
+  publicPoints: number;
+  juryCount: number;
+  juryScore: number | null;
+  points: number;
+} & Metadata;
@@ -0,0 +1,58 @@
+import classNames from "classnames";
+import { Helmet } from "react-helmet";
+import { Link } from "react-router-dom";
+
+import { formatAuthor } from "./Author";
+import { useCategory } from "./Category";
+import { useContestSlug } from "./ContestSlug";
+import LoadingView from "./LoadingView";
+import { useContestName, useMetadatas } from "./QueryHooks";
+
+export default function ImageListView() {
+  const slug = useContestSlug();
+  const category = useCategory();
+  const { data: contestName } = useContestName();
+  const { data: images, isLoading } = useMetadatas(category.slug);
+
+  if (isLoading || images == null) {
+    return <LoadingView />;
+  }
+
+  return (
+    <>
+      <Helmet>
+        <title>
+          {category.name} - {contestName}
+        </title>
+      </Helmet>
+      <div className="flex w-full flex-wrap gap-10 px-6 pb-10 pt-4">
+        {images.map((image) => (
+          <Link
+            key={image.id}
+            to={`/contest/${slug}/${category.slug}/${image.id}`}
+            className="w-full md:w-96"
+          >
+            <div className="card shadow-xl">
+              <figure>
+                <img
+                  src={`/api/contest/${slug}/${category.slug}/thumbnail/${image.id}`}
+                  alt={image.altTexts[0]}
+                  className={classNames("h-[200px]", image.isNsfw && "blur-lg")}
+                />
+              </figure>
+              <div className="card-body">
+                <h2 className="card-title">
+                  {image.isNsfw && (
+                    <span className="badge badge-secondary mr-2">NSFW</span>
+                  )}
+                  {image.title}
+                </h2>
+                <span>{formatAuthor(image)}</span>
+              </div>
+            </div>
+          </Link>
+        ))}
+      </div>
+    </>
+  );
+}
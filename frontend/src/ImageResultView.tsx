@@ -0,0 +1,72 @@
+import classNames from "classnames";
+import { Helmet } from "react-helmet";
+import { Link } from "react-router-dom";
+
+import { formatAuthor } from "./Author";
+import { useCategory } from "./Category";
+import { useContestSlug } from "./ContestSlug";
+import LoadingView from "./LoadingView";
+import { useContestName, useResultOpened, useResults } from "./QueryHooks";
+import ResultNotOpenedView from "./ResultNotOpenedView";
+
+export default function ImageResultView() {
+  const slug = useContestSlug();
+  const category = useCategory();
+  const { data: contestName } = useContestName();
+  const { data: opened, isLoading: isOpenedLoading } = useResultOpened();
+  const { data: images, isLoading } = useResults(category.slug);
+
+  if (isOpenedLoading || opened == null) {
+    return <LoadingView />;
+  }
+
+  if (!opened.opened) {
+    return <ResultNotOpenedView openAt={opened.openAt} />;
+  }
+
+  if (isLoading || images == null) {
+    return <LoadingView />;
+  }
+
+  return (
+    <>
+      <Helmet>
+        <title>
+          {category.name} - {contestName}
+        </title>
+      </Helmet>
+      <div className="flex w-full flex-wrap gap-10 px-6 pb-10 pt-4">
+        {images.map((image) => (
+          <Link
+            key={image.id}
+            to={`/contest/${slug}/${category.slug}/${image.id}`}
+            className="w-full md:w-96"
+          >
+            <div className="card shadow-xl">
+              <figure>
+                <img
+                  src={`/api/contest/${slug}/${category.slug}/thumbnail/${image.id}`}
+                  alt={image.altTexts[0]}
+                  className={classNames("h-[200px]", image.isNsfw && "blur-lg")}
+                />
+              </figure>
+              <div className="card-body">
+                <h2 className="card-title">
+                  <span className="badge badge-primary mr-2">
+                    {image.rank}위
+                  </span>
+                  <span className="badge mr-2">{image.voteCount}표</span>
+                  {image.isNsfw && (
+                    <span className="badge badge-secondary mr-2">NSFW</span>
+                  )}
+                  {image.title}
+                </h2>
+                <span>{formatAuthor(image)}</span>
+              </div>
+            </div>
+          </Link>
+        ))}
+      </div>
+    </>
+  );
+}
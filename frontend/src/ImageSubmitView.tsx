@@ -0,0 +1,207 @@
+import { FormEvent, useEffect, useState } from "react";
+import { Helmet } from "react-helmet";
+import { useNavigate } from "react-router-dom";
+
+import { useCategory } from "./Category";
+import { useContestSlug } from "./ContestSlug";
+import LoadingView from "./LoadingView";
+import { usePostImageMutation } from "./MutationHooks";
+import { useContestName, useSubmissionOpened } from "./QueryHooks";
+import SubmissionNotOpenedView from "./SubmissionNotOpenedView";
+
+export default function ImageSubmitView() {
+  const slug = useContestSlug();
+  const category = useCategory();
+  const navigate = useNavigate();
+
+  const { data: contestName } = useContestName();
+  const { data: opened, isLoading: isOpenedLoading } = useSubmissionOpened();
+
+  const [error, setError] = useState("");
+
+  const { mutate: postImage, isLoading: isPosting } = usePostImageMutation({
+    onSuccess: (resp) => {
+      navigate(`/contest/${slug}/${category.slug}/${resp.id}`);
+    },
+    onError: (error) => {
+      setError((error.response?.data as string) ?? error.message);
+    },
+  });
+
+  const [title, setTitle] = useState("");
+  const [isNsfw, setIsNsfw] = useState(false);
+  const [description, setDescription] = useState("");
+  const [files, setFiles] = useState<File[]>([]);
+  const [altTexts, setAltTexts] = useState<string[]>([]);
+  const [previews, setPreviews] = useState<string[]>([]);
+
+  useEffect(() => {
+    const previewUrls = files.map((file) => URL.createObjectURL(file));
+    setPreviews(previewUrls);
+
+    return () => previewUrls.forEach((url) => URL.revokeObjectURL(url));
+  }, [files]);
+
+  if (isOpenedLoading || opened == null) {
+    return <LoadingView />;
+  }
+
+  if (!opened.opened) {
+    return (
+      <SubmissionNotOpenedView
+        openAt={opened.openAt}
+        closeAt={opened.closeAt}
+      />
+    );
+  }
+
+  const isInvalid =
+    title === "" ||
+    title.length > category.titleMaxLength ||
+    description.length > category.descriptionMaxLength ||
+    files.length === 0 ||
+    files.length > category.pageLimit ||
+    files.some((file) => file.size > category.imageMaxSize) ||
+    altTexts.some((altText) => altText.length > category.altTextMaxLength);
+
+  const onSubmit = (e: FormEvent<HTMLFormElement>) => {
+    e.preventDefault();
+
+    if (isInvalid) {
+      return;
+    }
+
+    if (
+      window.confirm(
+        "제출합니다.\n제출 후에는 취소하거나 수정할 수 없습니다.\n제출하시겠습니까?"
+      )
+    ) {
+      postImage({
+        category: category.slug,
+        title,
+        description,
+        isNsfw,
+        files,
+        altTexts,
+      });
+    }
+  };
+
+  return (
+    <>
+      <Helmet>
+        <title>
+          {category.name} 제출 - {contestName}
+        </title>
+      </Helmet>
+      <div className="flex w-full justify-center px-6 pb-10 pt-4">
+        <form className="w-full md:w-2/3" onSubmit={onSubmit}>
+          <div className="mb-2">
+            <label className="label">
+              <label className="label-text">제목</label>
+              <label className="label-text-alt">
+                {title.length} / {category.titleMaxLength}
+              </label>
+            </label>
+            <input
+              type="text"
+              className="input input-bordered w-full"
+              value={title}
+              onChange={(e) => {
+                setTitle(e.target.value);
+              }}
+            />
+          </div>
+          <div className="mb-2">
+            <label className="label">
+              <label className="label-text" />
+              <label className="label-text-alt">
+                {Math.floor(category.imageMaxSize / 1024 / 1024)}MB 이하의
+                PNG, JPEG, WebP, GIF 파일을 {category.pageLimit}장까지
+                업로드할 수 있어요.
+              </label>
+            </label>
+            <input
+              type="file"
+              accept="image/png,image/jpeg,image/webp,image/gif"
+              multiple={category.pageLimit > 1}
+              className="file-input file-input-bordered w-full"
+              onChange={(e) => {
+                const selected = Array.from(e.target.files ?? []);
+                setFiles(selected);
+                setAltTexts(selected.map(() => ""));
+              }}
+            />
+          </div>
+          <div className="mb-2">
+            <label className="label w-fit cursor-pointer">
+              <input
+                type="checkbox"
+                className="checkbox"
+                checked={isNsfw}
+                onChange={(e) => {
+                  setIsNsfw(e.target.checked);
+                }}
+              />
+              <span className="label-text ml-2">NSFW</span>
+            </label>
+          </div>
+          <div className="mb-2">
+            <label className="label">
+              <label className="label-text">설명</label>
+              <label className="label-text-alt">
+                {description.length} / {category.descriptionMaxLength}
+              </label>
+            </label>
+            <textarea
+              className="textarea textarea-bordered h-[200px] w-full"
+              value={description}
+              onChange={(e) => {
+                setDescription(e.target.value);
+              }}
+            />
+          </div>
+          <input
+            type="submit"
+            className="btn btn-primary mb-2"
+            value="제출"
+            disabled={isPosting || isInvalid}
+          />
+          {files.map((file, i) => (
+            <div key={i} className="mb-4">
+              <label className="label">
+                <label className="label-text">
+                  {i + 1}번째 이미지 대체 텍스트
+                </label>
+                <label className="label-text-alt">
+                  {altTexts[i].length} / {category.altTextMaxLength}
+                </label>
+              </label>
+              <input
+                type="text"
+                className="input input-bordered mb-2 w-full"
+                value={altTexts[i]}
+                onChange={(e) => {
+                  const value = e.target.value;
+                  setAltTexts((prev) =>
+                    prev.map((altText, j) => (j === i ? value : altText))
+                  );
+                }}
+              />
+              {previews[i] && (
+                <img className="shadow-lg" src={previews[i]} alt={file.name} />
+              )}
+            </div>
+          ))}
+        </form>
+      </div>
+      {error !== "" && (
+        <div className="toast">
+          <div className="alert alert-error">
+            <span>{error}</span>
+          </div>
+        </div>
+      )}
+    </>
+  );
+}
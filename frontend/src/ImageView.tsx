@@ -1,59 +1,56 @@
+import classNames from "classnames";
 import { useState } from "react";
 import { Helmet } from "react-helmet";
 import { Link, useParams } from "react-router-dom";
 
+import { formatAuthor } from "./Author";
+import { useCategory } from "./Category";
+import { useContestSlug } from "./ContestSlug";
 import LoadingView from "./LoadingView";
-import { usePostLiteratureVoteMutation } from "./MutationHooks";
-import NotEnabledView from "./NotEnabledView";
+import { usePostVoteMutation } from "./MutationHooks";
 import NotFoundView from "./NotFoundView";
 import {
   useContestName,
-  useEnabled,
-  useLiterature,
-  useLiteratureVote,
+  useMetadata,
   useUserFromApi,
+  useVote,
   useVotingOpened,
 } from "./QueryHooks";
 
-export default function LiteratureView() {
+export default function ImageView() {
+  const slug = useContestSlug();
   const { id } = useParams();
+  const category = useCategory();
   const { data: contestName } = useContestName();
-  const { data: enabled, isLoading: isEnabledLoading } = useEnabled();
-  const { data: literature, isLoading } = useLiterature(Number(id));
+  const { data: image, isLoading } = useMetadata(category.slug, Number(id));
   const { data: voteOpened } = useVotingOpened();
   const { data: user } = useUserFromApi();
-  const { data: vote, refetch: refetchVote } = useLiteratureVote(
+  const { data: vote, refetch: refetchVote } = useVote(
     user,
+    category.slug,
     Number(id)
   );
 
   const [success, setSuccess] = useState("");
   const [error, setError] = useState("");
 
-  const { mutate: postVote, isLoading: isVoting } =
-    usePostLiteratureVoteMutation({
-      onSuccess: async () => {
-        await refetchVote();
-        setSuccess("투표했습니다.");
-      },
-      onError: (error) => {
-        setError((error.response?.data as string) ?? error.message);
-      },
-    });
+  const [isBlurRemoved, setIsBlurRemoved] = useState(false);
 
-  if (isEnabledLoading || enabled == null) {
-    return <LoadingView />;
-  }
-
-  if (!enabled.literature) {
-    return <NotEnabledView />;
-  }
+  const { mutate: postVote, isLoading: isVoting } = usePostVoteMutation({
+    onSuccess: async () => {
+      await refetchVote();
+      setSuccess("투표했습니다.");
+    },
+    onError: (error) => {
+      setError((error.response?.data as string) ?? error.message);
+    },
+  });
 
   if (isLoading) {
     return <LoadingView />;
   }
 
-  if (literature == null) {
+  if (image == null) {
     return <NotFoundView />;
   }
 
@@ -67,7 +64,7 @@ export default function LiteratureView() {
         "투표합니다.\n투표 후에는 취소할 수 없습니다.\n투표하시겠습니까?"
       )
     ) {
-      postVote({ id: Number(id) });
+      postVote({ category: category.slug, id: Number(id) });
     }
   };
 
@@ -75,26 +72,46 @@ export default function LiteratureView() {
     <>
       <Helmet>
         <title>
-          {literature.title} - {literature.authorHandle}@
-          {literature.authorInstance} - {contestName}
+          {image.title} - {formatAuthor(image)} - {contestName}
         </title>
       </Helmet>
       <div className="flex w-full justify-center px-6 pb-10 pt-4">
         <div className="w-full md:w-2/3">
           <h2 className="mb-4 text-xl">
-            {literature.isNsfw && (
+            {image.isNsfw && (
               <span className="badge badge-secondary mr-2">NSFW</span>
             )}
-            {literature.title}
+            {image.title}
+          </h2>
+          <h2 className="mb-4">
+            {image.authorHandle != null && image.authorInstance != null ? (
+              <Link
+                to={`https://${image.authorInstance}/@${image.authorHandle}`}
+              >
+                {formatAuthor(image)}
+              </Link>
+            ) : (
+              formatAuthor(image)
+            )}
           </h2>
-          <h3 className="mb-4">
-            <Link
-              to={`https://${literature.authorInstance}/@${literature.authorHandle}`}
-            >
-              {literature.authorHandle}@{literature.authorInstance}
-            </Link>
-          </h3>
-          <p className="mb-4 whitespace-pre-line">{literature.text}</p>
+          {image.altTexts.map((altText, i) => (
+            <img
+              key={i}
+              src={`/api/contest/${slug}/${category.slug}/${image.id}/${i + 1}`}
+              alt={altText}
+              className={classNames(
+                "mb-4 w-full md:w-fit",
+                image.isNsfw && "cursor-pointer",
+                image.isNsfw && !isBlurRemoved && "blur-lg"
+              )}
+              onClick={() => {
+                if (image.isNsfw) {
+                  setIsBlurRemoved((b) => !b);
+                }
+              }}
+            />
+          ))}
+          <p className="mb-4 whitespace-pre-line">{image.description}</p>
           <div className="divider" />
           {voteOpened?.opened ? (
             user != null ? (
@@ -104,14 +121,16 @@ export default function LiteratureView() {
                   disabled={
                     isVoting ||
                     vote?.voted ||
-                    (literature.authorHandle === user.handle &&
-                      literature.authorInstance === user.instance)
+                    (image.authorHandle === user.handle &&
+                      image.authorInstance === user.instance)
                   }
                   onClick={onVote}
                 >
                   투표하기
                 </button>
-                <span>현재 자신의 투표 수: {vote?.voteCount} / 5</span>
+                <span>
+                  현재 자신의 투표 수: {vote?.voteCount} / {category.voteLimit}
+                </span>
               </div>
             ) : (
               <div>
@@ -1,62 +1,52 @@
 import { Helmet } from "react-helmet";
 import { Link } from "react-router-dom";
 
-import { useContestName, useEnabled } from "./QueryHooks";
+import { useContestSlug } from "./ContestSlug";
+import { useContestName, useRules } from "./QueryHooks";
 
 export default function MainView() {
+  const slug = useContestSlug();
   const { data: contestName } = useContestName();
-  const { data: enabled } = useEnabled();
+  const { data: rules } = useRules();
 
   return (
     <>
       <Helmet>
         <title>{contestName}</title>
       </Helmet>
-      <div className="flex w-full justify-center p-10">
-        {enabled?.literature && (
-          <ul className="menu w-56">
-            <li className="menu-title">
-              <h2 className="text-xl">글</h2>
-            </li>
-            <li>
-              <Link to="/literature/submit" className="text-lg">
-                출품
-              </Link>
-            </li>
-            <li>
-              <Link to="/literature" className="text-lg">
-                감상 / 투표
-              </Link>
-            </li>
-            <li>
-              <Link to="/literature/result" className="text-lg">
-                결과 확인
-              </Link>
-            </li>
-          </ul>
-        )}
-        {enabled?.art && (
-          <ul className="menu w-56">
-            <li className="menu-title">
-              <h2 className="text-xl">그림</h2>
-            </li>
-            <li>
-              <Link to="/art/submit" className="text-lg">
-                출품
-              </Link>
-            </li>
-            <li>
-              <Link to="/art" className="text-lg">
-                감상 / 투표
-              </Link>
-            </li>
-            <li>
-              <Link to="/art/result" className="text-lg">
-                결과 확인
-              </Link>
-            </li>
-          </ul>
-        )}
+      <div className="flex w-full flex-wrap justify-center p-10">
+        {rules != null &&
+          Object.values(rules).map((category) => (
+            <ul key={category.slug} className="menu w-56">
+              <li className="menu-title">
+                <h2 className="text-xl">{category.name}</h2>
+              </li>
+              <li>
+                <Link
+                  to={`/contest/${slug}/${category.slug}/submit`}
+                  className="text-lg"
+                >
+                  출품
+                </Link>
+              </li>
+              <li>
+                <Link
+                  to={`/contest/${slug}/${category.slug}`}
+                  className="text-lg"
+                >
+                  감상 / 투표
+                </Link>
+              </li>
+              <li>
+                <Link
+                  to={`/contest/${slug}/${category.slug}/result`}
+                  className="text-lg"
+                >
+                  결과 확인
+                </Link>
+              </li>
+            </ul>
+          ))}
       </div>
     </>
   );
@@ -6,13 +6,13 @@ import {
 } from "react-query";
 
 import { useAxiosClient } from "./AxiosContext";
+import { useContestSlug } from "./ContestSlug";
 import {
-  ArtMetadata,
-  Literature,
-  PostArtReq,
-  PostLiteratureReq,
+  Metadata,
+  PostImageReq,
   PostOauthAuthorizeReq,
   PostOauthAuthorizeResp,
+  PostTextReq,
   PostVoteReq,
 } from "./HttpTypes";
 
@@ -40,51 +40,59 @@ export function useOauthAuthorizeMutation(
   }, options);
 }
 
-export function usePostLiteratureMutation(
-  options?: MutationOption<PostLiteratureReq, Literature>
-): MutationRet<PostLiteratureReq, Literature> {
+export function useOauthLogoutMutation(
+  options?: MutationOption<void>
+): MutationRet<void> {
   const client = useAxiosClient();
-  return useMutation(async (payload: PostLiteratureReq) => {
-    const resp = await client.post<Literature>(
-      "/api/contest/submission/literature",
+  return useMutation(async () => {
+    await client.post("/api/oauth/logout");
+  }, options);
+}
+
+export function usePostTextMutation(
+  options?: MutationOption<PostTextReq, Metadata>
+): MutationRet<PostTextReq, Metadata> {
+  const client = useAxiosClient();
+  const slug = useContestSlug();
+  return useMutation(async ({ category, ...payload }: PostTextReq) => {
+    const resp = await client.post<Metadata>(
+      `/api/contest/${slug}/submission/${category}`,
       payload
     );
     return resp.data;
   }, options);
 }
 
-export function usePostArtMutation(
-  options?: MutationOption<PostArtReq, ArtMetadata>
-): MutationRet<PostArtReq, ArtMetadata> {
+export function usePostImageMutation(
+  options?: MutationOption<PostImageReq, Metadata>
+): MutationRet<PostImageReq, Metadata> {
   const client = useAxiosClient();
-  return useMutation(async (payload: PostArtReq) => {
+  const slug = useContestSlug();
+  return useMutation(async (payload: PostImageReq) => {
     const formData = new FormData();
     formData.append("title", payload.title);
     formData.append("description", payload.description);
     formData.append("isNsfw", payload.isNsfw ? "true" : "false");
-    formData.append("data", payload.file);
-    const resp = await client.post<ArtMetadata>(
-      "/api/contest/submission/art",
+    payload.files.forEach((file, i) => {
+      formData.append("data", file);
+      formData.append("altText", payload.altTexts[i] ?? "");
+    });
+    const resp = await client.post<Metadata>(
+      `/api/contest/${slug}/submission/${payload.category}`,
       formData
     );
     return resp.data;
   }, options);
 }
 
-export function usePostLiteratureVoteMutation(
-  options?: MutationOption<PostVoteReq, void>
-): MutationRet<PostVoteReq, void> {
-  const client = useAxiosClient();
-  return useMutation(async (payload: PostVoteReq) => {
-    await client.post(`/api/contest/voting/literature/${payload.id}`);
-  }, options);
-}
-
-export function usePostArtVoteMutation(
+export function usePostVoteMutation(
   options?: MutationOption<PostVoteReq, void>
 ): MutationRet<PostVoteReq, void> {
   const client = useAxiosClient();
+  const slug = useContestSlug();
   return useMutation(async (payload: PostVoteReq) => {
-    await client.post(`/api/contest/voting/art/${payload.id}`);
+    await client.post(
+      `/api/contest/${slug}/voting/${payload.category}/${payload.id}`
+    );
   }, options);
 }
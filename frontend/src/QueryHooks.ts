@@ -2,16 +2,18 @@ import { AxiosError, AxiosInstance, isAxiosError } from "axios";
 import { UseQueryResult, useQuery } from "react-query";
 
 import { useAxiosClient } from "./AxiosContext";
+import { useContestSlug } from "./ContestSlug";
 import {
-  ArtMetadata,
+  Contest,
   GetEnabledResp,
   GetOpenedResp,
   GetResultOpenedResp,
-  Literature,
-  LiteratureMetadata,
+  GetRulesResp,
+  Metadata,
+  Standing,
+  TextContent,
   User,
   Vote,
-  WithVoteCount,
 } from "./HttpTypes";
 
 async function get<T>(
@@ -34,19 +36,51 @@ async function get<T>(
   }
 }
 
-export function useContestName(): UseQueryResult<string, AxiosError> {
+export function useContests(): UseQueryResult<Contest[], AxiosError> {
   const client = useAxiosClient();
-  return useQuery(["contest/name"], async () => {
-    const resp = await get<string>(client, "/api/contest/name");
-    return resp;
+  return useQuery(["contests"], async () => {
+    return await get<Contest[]>(client, "/api/contest");
   });
 }
+
+export function useContestName(): UseQueryResult<string, AxiosError> {
+  const client = useAxiosClient();
+  const slug = useContestSlug();
+  return useQuery(
+    ["contest/name", slug],
+    async () => {
+      const resp = await get<string>(client, `/api/contest/${slug}/name`);
+      return resp;
+    },
+    { enabled: slug != null }
+  );
+}
 export function useEnabled(): UseQueryResult<GetEnabledResp, AxiosError> {
   const client = useAxiosClient();
-  return useQuery(["contest/enabled"], async () => {
-    const resp = await get<GetEnabledResp>(client, "/api/contest/enabled");
-    return resp;
-  });
+  const slug = useContestSlug();
+  return useQuery(
+    ["contest/enabled", slug],
+    async () => {
+      const resp = await get<GetEnabledResp>(
+        client,
+        `/api/contest/${slug}/enabled`
+      );
+      return resp;
+    },
+    { enabled: slug != null }
+  );
+}
+
+export function useRules(): UseQueryResult<GetRulesResp, AxiosError> {
+  const client = useAxiosClient();
+  const slug = useContestSlug();
+  return useQuery(
+    ["contest/rules", slug],
+    async () => {
+      return await get<GetRulesResp>(client, `/api/contest/${slug}/rules`);
+    },
+    { enabled: slug != null }
+  );
 }
 
 export function useUserFromApi(): UseQueryResult<User, AxiosError> {
@@ -65,79 +99,82 @@ export function useSubmissionOpened(): UseQueryResult<
   AxiosError
 > {
   const client = useAxiosClient();
-  return useQuery(["contest/submission/opened"], async () => {
-    return await get<GetOpenedResp>(client, "/api/contest/submission/opened");
+  const slug = useContestSlug();
+  return useQuery(["contest/submission/opened", slug], async () => {
+    return await get<GetOpenedResp>(
+      client,
+      `/api/contest/${slug}/submission/opened`
+    );
   });
 }
 
 export function useVotingOpened(): UseQueryResult<GetOpenedResp, AxiosError> {
   const client = useAxiosClient();
-  return useQuery(["contest/voting/opened"], async () => {
-    return await get<GetOpenedResp>(client, "/api/contest/voting/opened");
+  const slug = useContestSlug();
+  return useQuery(["contest/voting/opened", slug], async () => {
+    return await get<GetOpenedResp>(
+      client,
+      `/api/contest/${slug}/voting/opened`
+    );
   });
 }
 
-export function useLiterature(
+export function useTextContent(
+  category: string,
   id: number
-): UseQueryResult<Literature, AxiosError> {
+): UseQueryResult<TextContent, AxiosError> {
   const client = useAxiosClient();
-  return useQuery(["contest/literature", id], async () => {
-    return await get<Literature>(client, `/api/contest/literature/${id}`);
-  });
-}
-
-export function useLiteratureMetadatas(): UseQueryResult<
-  LiteratureMetadata[],
-  AxiosError
-> {
-  const client = useAxiosClient();
-  return useQuery(["contest/literature/metadatas"], async () => {
-    return await get<LiteratureMetadata[]>(
+  const slug = useContestSlug();
+  return useQuery(["contest/content", slug, category, id], async () => {
+    return await get<TextContent>(
       client,
-      "/api/contest/literature/metadata"
+      `/api/contest/${slug}/${category}/${id}`
     );
   });
 }
 
-export function useArtMetadata(
+export function useMetadata(
+  category: string,
   id: number
-): UseQueryResult<ArtMetadata, AxiosError> {
-  const client = useAxiosClient();
-  return useQuery(["contest/art/metadata", id], async () => {
-    return await get<ArtMetadata>(client, `/api/contest/art/metadata/${id}`);
-  });
-}
-
-export function useArtMetadatas(): UseQueryResult<ArtMetadata[], AxiosError> {
+): UseQueryResult<Metadata, AxiosError> {
   const client = useAxiosClient();
-  return useQuery(["contest/art/metadatas"], async () => {
-    return await get<ArtMetadata[]>(client, "/api/contest/art/metadata");
+  const slug = useContestSlug();
+  return useQuery(["contest/metadata", slug, category, id], async () => {
+    return await get<Metadata>(
+      client,
+      `/api/contest/${slug}/${category}/metadata/${id}`
+    );
   });
 }
 
-export function useLiteratureVote(
-  user: User | undefined,
-  id: number
-): UseQueryResult<Vote, AxiosError> {
+export function useMetadatas(
+  category: string
+): UseQueryResult<Metadata[], AxiosError> {
   const client = useAxiosClient();
-  return useQuery(["contestt/voting/literature", user, id], async () => {
-    if (user == null) {
-      return undefined;
-    }
-    return await get<Vote>(client, `/api/contest/voting/literature/${id}`);
+  const slug = useContestSlug();
+  return useQuery(["contest/metadatas", slug, category], async () => {
+    return await get<Metadata[]>(
+      client,
+      `/api/contest/${slug}/${category}/metadata`
+    );
   });
 }
 
-export function useArtVote(
+export function useVote(
   user: User | undefined,
+  category: string,
   id: number
 ): UseQueryResult<Vote, AxiosError> {
   const client = useAxiosClient();
-  return useQuery(["contestt/voting/art", user, id], async () => {
+  const slug = useContestSlug();
+  return useQuery(["contest/voting", slug, category, user, id], async () => {
     if (user == null) {
       return undefined;
     }
-    return await get<Vote>(client, `/api/contest/voting/art/${id}`);
+    return await get<Vote>(
+      client,
+      `/api/contest/${slug}/voting/${category}/${id}`
+    );
   });
 }
 
@@ -146,33 +183,24 @@ export function useResultOpened(): UseQueryResult<
   AxiosError
 > {
   const client = useAxiosClient();
-  return useQuery(["contest/result/opened"], async () => {
-    return await get<GetOpenedResp>(client, "/api/contest/result/opened");
-  });
-}
-
-export function useLiteratureResults(): UseQueryResult<
-  WithVoteCount<LiteratureMetadata>[],
-  AxiosError
-> {
-  const client = useAxiosClient();
-  return useQuery(["contest/result/literature"], async () => {
-    return await get<WithVoteCount<LiteratureMetadata>[]>(
+  const slug = useContestSlug();
+  return useQuery(["contest/result/opened", slug], async () => {
+    return await get<GetOpenedResp>(
       client,
-      "/api/contest/result/literature"
+      `/api/contest/${slug}/result/opened`
     );
   });
 }
 
-export function useArtResults(): UseQueryResult<
-  WithVoteCount<ArtMetadata>[],
-  AxiosError
-> {
+export function useResults(
+  category: string
+): UseQueryResult<Standing[], AxiosError> {
   const client = useAxiosClient();
-  return useQuery(["contest/result/art"], async () => {
-    return await get<WithVoteCount<ArtMetadata>[]>(
+  const slug = useContestSlug();
+  return useQuery(["contest/result", slug, category], async () => {
+    return await get<Standing[]>(
       client,
-      "/api/contest/result/art"
+      `/api/contest/${slug}/result/${category}`
     );
   });
 }
@@ -0,0 +1,43 @@
+import { Helmet } from "react-helmet";
+import { Link } from "react-router-dom";
+
+import { formatAuthor } from "./Author";
+import { useCategory } from "./Category";
+import { useContestSlug } from "./ContestSlug";
+import LoadingView from "./LoadingView";
+import { useContestName, useMetadatas } from "./QueryHooks";
+
+export default function TextListView() {
+  const slug = useContestSlug();
+  const category = useCategory();
+  const { data: contestName } = useContestName();
+  const { data: texts, isLoading } = useMetadatas(category.slug);
+
+  if (isLoading || texts == null) {
+    return <LoadingView />;
+  }
+
+  return (
+    <>
+      <Helmet>
+        <title>
+          {category.name} - {contestName}
+        </title>
+      </Helmet>
+      <div className="flex w-full justify-center px-6 pb-10 pt-4">
+        <ul className="w-full md:w-2/3">
+          {texts.map((text) => (
+            <li key={text.id} className="p-2">
+              <Link to={`/contest/${slug}/${category.slug}/${text.id}`}>
+                {text.isNsfw && (
+                  <span className="badge badge-secondary mr-2">NSFW</span>
+                )}
+                {formatAuthor(text)} - {text.title}
+              </Link>
+            </li>
+          ))}
+        </ul>
+      </div>
+    </>
+  );
+}
@@ -1,25 +1,28 @@
-import { FormEvent, useEffect, useState } from "react";
+import { FormEvent, useState } from "react";
 import { Helmet } from "react-helmet";
 import { useNavigate } from "react-router-dom";
 
+import { useCategory } from "./Category";
+import { useContestSlug } from "./ContestSlug";
+import { TextFormat } from "./HttpTypes";
 import LoadingView from "./LoadingView";
-import { usePostArtMutation } from "./MutationHooks";
-import NotEnabledView from "./NotEnabledView";
-import { useContestName, useEnabled, useSubmissionOpened } from "./QueryHooks";
+import { usePostTextMutation } from "./MutationHooks";
+import { useContestName, useSubmissionOpened } from "./QueryHooks";
 import SubmissionNotOpenedView from "./SubmissionNotOpenedView";
 
-export default function ArtSubmitView() {
+export default function TextSubmitView() {
+  const slug = useContestSlug();
+  const category = useCategory();
   const navigate = useNavigate();
 
   const { data: contestName } = useContestName();
-  const { data: enabled, isLoading: isEnabledLoading } = useEnabled();
   const { data: opened, isLoading: isOpenedLoading } = useSubmissionOpened();
 
   const [error, setError] = useState("");
 
-  const { mutate: postArt, isLoading: isPosting } = usePostArtMutation({
+  const { mutate: postText, isLoading: isPosting } = usePostTextMutation({
     onSuccess: (resp) => {
-      navigate(`/art/${resp.id}`);
+      navigate(`/contest/${slug}/${category.slug}/${resp.id}`);
     },
     onError: (error) => {
       setError((error.response?.data as string) ?? error.message);
@@ -29,28 +32,8 @@ export default function ArtSubmitView() {
   const [title, setTitle] = useState("");
   const [isNsfw, setIsNsfw] = useState(false);
   const [description, setDescription] = useState("");
-  const [file, setFile] = useState<File | undefined>(undefined);
-  const [preview, setPreview] = useState<string | undefined>(undefined);
-
-  useEffect(() => {
-    if (!file) {
-      setPreview(undefined);
-      return;
-    }
-
-    const previewUrl = URL.createObjectURL(file);
-    setPreview(previewUrl);
-
-    return () => URL.revokeObjectURL(previewUrl);
-  }, [file]);
-
-  if (isEnabledLoading || enabled == null) {
-    return <LoadingView />;
-  }
-
-  if (!enabled.art) {
-    return <NotEnabledView />;
-  }
+  const [text, setText] = useState("");
+  const [format, setFormat] = useState<TextFormat>("plain");
 
   if (isOpenedLoading || opened == null) {
     return <LoadingView />;
@@ -65,13 +48,13 @@ export default function ArtSubmitView() {
     );
   }
 
+  // Markdown is counted without its markup, which only the server can do.
   const isInvalid =
     title === "" ||
-    title.length > 100 ||
-    description === "" ||
-    description.length > 2000 ||
-    file == null ||
-    file.size > 1024 * 1024 * 50;
+    text === "" ||
+    title.length > category.titleMaxLength ||
+    description.length > category.descriptionMaxLength ||
+    (format === "plain" && text.length > category.textMaxLength);
 
   const onSubmit = (e: FormEvent<HTMLFormElement>) => {
     e.preventDefault();
@@ -85,21 +68,32 @@ export default function ArtSubmitView() {
         "제출합니다.\n제출 후에는 취소하거나 수정할 수 없습니다.\n제출하시겠습니까?"
       )
     ) {
-      postArt({ title, description, isNsfw, file });
+      postText({
+        category: category.slug,
+        title,
+        description,
+        text,
+        format,
+        isNsfw,
+      });
     }
   };
 
   return (
     <>
       <Helmet>
-        <title>그림 제출 - {contestName}</title>
+        <title>
+          {category.name} 제출 - {contestName}
+        </title>
       </Helmet>
       <div className="flex w-full justify-center px-6 pb-10 pt-4">
         <form className="w-full md:w-2/3" onSubmit={onSubmit}>
           <div className="mb-2">
             <label className="label">
               <label className="label-text">제목</label>
-              <label className="label-text-alt">{title.length} / 100</label>
+              <label className="label-text-alt">
+                {title.length} / {category.titleMaxLength}
+              </label>
             </label>
             <input
               type="text"
@@ -110,22 +104,6 @@ export default function ArtSubmitView() {
               }}
             />
           </div>
-          <div className="mb-2">
-            <label className="label">
-              <label className="label-text" />
-              <label className="label-text-alt">
-                50MB 이하의 PNG 파일만 업로드할 수 있어요.
-              </label>
-            </label>
-            <input
-              type="file"
-              accept="image/png"
-              className="file-input file-input-bordered w-full"
-              onChange={(e) => {
-                setFile(e.target.files?.[0]);
-              }}
-            />
-          </div>
           <div className="mb-2">
             <label className="label w-fit cursor-pointer">
               <input
@@ -139,32 +117,57 @@ export default function ArtSubmitView() {
               <span className="label-text ml-2">NSFW</span>
             </label>
           </div>
+          {category.descriptionMaxLength > 0 && (
+            <div className="mb-2">
+              <label className="label">
+                <label className="label-text">설명</label>
+                <label className="label-text-alt">
+                  {description.length} / {category.descriptionMaxLength}
+                </label>
+              </label>
+              <textarea
+                className="textarea textarea-bordered h-[100px] w-full"
+                value={description}
+                onChange={(e) => {
+                  setDescription(e.target.value);
+                }}
+              />
+            </div>
+          )}
           <div className="mb-2">
             <label className="label">
-              <label className="label-text">설명</label>
+              <label className="label-text">내용</label>
               <label className="label-text-alt">
-                {description.length} / 2000
+                {text.length} / {category.textMaxLength}
               </label>
             </label>
             <textarea
-              className="textarea textarea-bordered h-[200px] w-full"
-              value={description}
+              className="textarea textarea-bordered h-[500px] w-full"
+              value={text}
               onChange={(e) => {
-                setDescription(e.target.value);
+                setText(e.target.value);
               }}
             />
           </div>
+          <div className="mb-2">
+            <label className="label w-fit cursor-pointer">
+              <input
+                type="checkbox"
+                className="checkbox"
+                checked={format === "markdown"}
+                onChange={(e) => {
+                  setFormat(e.target.checked ? "markdown" : "plain");
+                }}
+              />
+              <span className="label-text ml-2">마크다운</span>
+            </label>
+          </div>
           <input
             type="submit"
-            className="btn btn-primary mb-2"
+            className="btn btn-primary"
             value="제출"
             disabled={isPosting || isInvalid}
           />
-          {file && preview && (
-            <div>
-              <img className="shadow-lg" src={preview} alt={title} />
-            </div>
-          )}
         </form>
       </div>
       {error !== "" && (
@@ -1,36 +1,37 @@
-import classNames from "classnames";
 import { useState } from "react";
 import { Helmet } from "react-helmet";
 import { Link, useParams } from "react-router-dom";
 
+import { formatAuthor } from "./Author";
+import { useCategory } from "./Category";
 import LoadingView from "./LoadingView";
-import { usePostArtVoteMutation } from "./MutationHooks";
-import NotEnabledView from "./NotEnabledView";
+import { usePostVoteMutation } from "./MutationHooks";
 import NotFoundView from "./NotFoundView";
 import {
-  useArtMetadata,
-  useArtVote,
   useContestName,
-  useEnabled,
+  useTextContent,
   useUserFromApi,
+  useVote,
   useVotingOpened,
 } from "./QueryHooks";
 
-export default function ArtView() {
+export default function TextView() {
   const { id } = useParams();
+  const category = useCategory();
   const { data: contestName } = useContestName();
-  const { data: enabled, isLoading: isEnabledLoading } = useEnabled();
-  const { data: art, isLoading } = useArtMetadata(Number(id));
+  const { data: text, isLoading } = useTextContent(category.slug, Number(id));
   const { data: voteOpened } = useVotingOpened();
   const { data: user } = useUserFromApi();
-  const { data: vote, refetch: refetchVote } = useArtVote(user, Number(id));
+  const { data: vote, refetch: refetchVote } = useVote(
+    user,
+    category.slug,
+    Number(id)
+  );
 
   const [success, setSuccess] = useState("");
   const [error, setError] = useState("");
 
-  const [isBlurRemoved, setIsBlurRemoved] = useState(false);
-
-  const { mutate: postVote, isLoading: isVoting } = usePostArtVoteMutation({
+  const { mutate: postVote, isLoading: isVoting } = usePostVoteMutation({
     onSuccess: async () => {
       await refetchVote();
       setSuccess("투표했습니다.");
@@ -40,19 +41,11 @@ export default function ArtView() {
     },
   });
 
-  if (isEnabledLoading || enabled == null) {
-    return <LoadingView />;
-  }
-
-  if (!enabled.art) {
-    return <NotEnabledView />;
-  }
-
   if (isLoading) {
     return <LoadingView />;
   }
 
-  if (art == null) {
+  if (text == null) {
     return <NotFoundView />;
   }
 
@@ -66,7 +59,7 @@ export default function ArtView() {
         "투표합니다.\n투표 후에는 취소할 수 없습니다.\n투표하시겠습니까?"
       )
     ) {
-      postVote({ id: Number(id) });
+      postVote({ category: category.slug, id: Number(id) });
     }
   };
 
@@ -74,37 +67,35 @@ export default function ArtView() {
     <>
       <Helmet>
         <title>
-          {art.title} - {art.authorHandle}@{art.authorInstance} - {contestName}
+          {text.title} - {formatAuthor(text)} - {contestName}
         </title>
       </Helmet>
       <div className="flex w-full justify-center px-6 pb-10 pt-4">
         <div className="w-full md:w-2/3">
           <h2 className="mb-4 text-xl">
-            {art.isNsfw && (
+            {text.isNsfw && (
               <span className="badge badge-secondary mr-2">NSFW</span>
             )}
-            {art.title}
-          </h2>
-          <h2 className="mb-4">
-            <Link to={`https://${art.authorInstance}/@${art.authorHandle}`}>
-              {art.authorHandle}@{art.authorInstance}
-            </Link>
+            {text.title}
           </h2>
-          <img
-            src={`/api/contest/art/${art.id}`}
-            alt={art.title}
-            className={classNames(
-              "mb-4 w-full md:w-fit",
-              art.isNsfw && "cursor-pointer",
-              art.isNsfw && !isBlurRemoved && "blur-lg"
+          <h3 className="mb-4">
+            {text.authorHandle != null && text.authorInstance != null ? (
+              <Link
+                to={`https://${text.authorInstance}/@${text.authorHandle}`}
+              >
+                {formatAuthor(text)}
+              </Link>
+            ) : (
+              formatAuthor(text)
             )}
-            onClick={() => {
-              if (art.isNsfw) {
-                setIsBlurRemoved((b) => !b);
-              }
-            }}
+          </h3>
+          {text.description !== "" && (
+            <p className="mb-4 whitespace-pre-line">{text.description}</p>
+          )}
+          <div
+            className="mb-4 [&>p]:mb-4"
+            dangerouslySetInnerHTML={{ __html: text.html }}
           />
-          <p className="mb-4 whitespace-pre-line">{art.description}</p>
           <div className="divider" />
           {voteOpened?.opened ? (
             user != null ? (
@@ -114,14 +105,16 @@ export default function ArtView() {
                   disabled={
                     isVoting ||
                     vote?.voted ||
-                    (art.authorHandle === user.handle &&
-                      art.authorInstance === user.instance)
+                    (text.authorHandle === user.handle &&
+                      text.authorInstance === user.instance)
                   }
                   onClick={onVote}
                 >
                   투표하기
                 </button>
-                <span>현재 자신의 투표 수: {vote?.voteCount} / 5</span>
+                <span>
+                  현재 자신의 투표 수: {vote?.voteCount} / {category.voteLimit}
+                </span>
               </div>
             ) : (
               <div>
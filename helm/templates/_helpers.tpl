@@ -60,3 +60,10 @@ Create the name of the service account to use
 {{- default "default" .Values.serviceAccount.name }}
 {{- end }}
 {{- end }}
+
+{{/*
+Name of the claim backing the local storage backend, created by the chart unless an existing one is set
+*/}}
+{{- define "contcont.storageClaimName" -}}
+{{- default (printf "%s-storage" (include "contcont.fullname" .)) .Values.config.storage.persistentVolumeClaim }}
+{{- end }}